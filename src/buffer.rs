@@ -1,8 +1,18 @@
 use super::*;
-use simd_util::simd::num::SimdFloat;
+use alloc::alloc::Global;
+use core::{
+    alloc::{Allocator, Layout},
+    marker::PhantomData,
+    ops::{Add, AddAssign, Mul, Range},
+    ptr::NonNull,
+};
+use simd_util::simd::{num::SimdFloat, LaneCount, Simd, SimdElement, SupportedLaneCount};
 
-pub struct BufferList<T, U> {
-    buffers: Box<[(Box<[T]>, U)]>,
+/// A buffer, along with its user data
+type Buffer<T, U, A> = (Box<[T], A>, U);
+
+pub struct BufferList<T, U, A: Allocator = Global> {
+    buffers: Box<[Buffer<T, U, A>]>,
     buf_len: NonZeroUsize,
 }
 
@@ -39,6 +49,33 @@ impl<T, U> BufferList<T, U> {
         unsafe { Self::new_with(num_buffers, buf_len, U::default) }
     }
 
+    /// Fallible version of [`Self::new_with`]
+    ///
+    /// # Safety
+    ///
+    /// Same as [`Self::new_with`]
+    #[inline]
+    pub unsafe fn try_new_with(
+        num_buffers: usize,
+        buf_len: NonZeroUsize,
+        mut f: impl FnMut() -> U,
+    ) -> Result<Self, AllocError> {
+        Ok(Self {
+            buffers: try_boxed_slice(num_buffers, || {
+                Ok((Box::try_new_zeroed_slice(buf_len.get())?.assume_init(), f()))
+            })?,
+            buf_len,
+        })
+    }
+
+    /// Number of heap bytes allocated by [`Self::new_with`] with these arguments
+    #[inline]
+    pub const fn memory_requirements(num_buffers: usize, buf_len: NonZeroUsize) -> usize {
+        num_buffers * (mem::size_of::<(Box<[T]>, U)>() + buf_len.get() * mem::size_of::<T>())
+    }
+}
+
+impl<T, U, A: Allocator> BufferList<T, U, A> {
     #[inline]
     pub fn get(&self, index: usize) -> Option<(&[T], &U)> {
         self.buffers
@@ -54,12 +91,57 @@ impl<T, U> BufferList<T, U> {
     }
 
     #[inline]
-    pub fn range_mut(&mut self, start: usize, len: NonZeroUsize) -> Option<BufferListRefMut<T, U>> {
-        (start + len.get() <= self.buf_len.get()).then_some(BufferListRefMut {
-            buffers: self.buffers.as_mut(),
-            start,
-            len,
-        })
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    #[inline]
+    pub fn buf_len(&self) -> NonZeroUsize {
+        self.buf_len
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&[T], &U)> {
+        self.buffers.iter().map(|(buf, mask)| (buf.as_ref(), mask))
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl ExactSizeIterator<Item = (&mut [T], &mut U)> {
+        self.buffers
+            .iter_mut()
+            .map(|(buf, mask)| (buf.as_mut(), mask))
+    }
+
+    /// Sets every sample of every buffer to `value`, without reallocating
+    #[inline]
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        for (buf, _) in self.iter_mut() {
+            buf.fill(value.clone());
+        }
+    }
+
+    #[inline]
+    pub fn range_mut(
+        &mut self,
+        start: usize,
+        len: NonZeroUsize,
+    ) -> Option<BufferListRefMut<T, U, A>> {
+        start
+            .checked_add(len.get())
+            .is_some_and(|end| end <= self.buf_len.get())
+            .then_some(BufferListRefMut {
+                buffers: self.buffers.as_mut(),
+                start,
+                len,
+            })
     }
 }
 
@@ -83,22 +165,89 @@ impl<T: SimdFloat, U> BufferList<T, U> {
     {
         Self::new_vfloat_with(num_buffers, buf_len, Default::default)
     }
+
+    #[inline]
+    pub fn try_new_vfloat_with(
+        num_buffers: usize,
+        buf_len: NonZeroUsize,
+        f: impl FnMut() -> U,
+    ) -> Result<Self, AllocError> {
+        // SAFETY: see `Self::new_vfloat_with`
+        unsafe { Self::try_new_with(num_buffers, buf_len, f) }
+    }
+}
+
+impl<T: SimdElement, U, const N: usize> BufferList<T, U, AlignedAs<Simd<T, N>>>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    /// Like [`BufferList::new_with`], but every buffer is aligned for `Simd<T, N>`,
+    /// so [`Self::get_simd`] succeeds for any buffer, as long as `buf_len` is a
+    /// multiple of `N`.
+    #[inline]
+    #[must_use]
+    pub fn new_aligned_with(
+        num_buffers: usize,
+        buf_len: NonZeroUsize,
+        mut f: impl FnMut() -> U,
+    ) -> Self {
+        Self {
+            buffers: iter::repeat_with(|| {
+                let buf = Box::new_zeroed_slice_in(buf_len.get(), AlignedAs::new());
+                // SAFETY: SIMD elements (integers, floats and raw pointers) are safely zeroable
+                (unsafe { buf.assume_init() }, f())
+            })
+            .take(num_buffers)
+            .collect(),
+            buf_len,
+        }
+    }
+}
+
+impl<T: SimdElement, U, A: Allocator> BufferList<T, U, A> {
+    /// Reinterprets the buffer at `index` as a slice of vectors. Returns `None` if
+    /// it doesn't exist, or if its length or alignment don't allow it. Buffers of scalars
+    /// are only guaranteed to be aligned for vectors when created with
+    /// [`BufferList::new_aligned_with`].
+    #[inline]
+    pub fn get_simd<const N: usize>(&self, index: usize) -> Option<&[Simd<T, N>]>
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        self.get(index).and_then(|(buf, _)| match buf.as_simd() {
+            ([], vectors, []) => Some(vectors),
+            _ => None,
+        })
+    }
+
+    /// Mutable version of [`Self::get_simd`]
+    #[inline]
+    pub fn get_simd_mut<const N: usize>(&mut self, index: usize) -> Option<&mut [Simd<T, N>]>
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        self.get_mut(index)
+            .and_then(|(buf, _)| match buf.as_simd_mut() {
+                ([], vectors, []) => Some(vectors),
+                _ => None,
+            })
+    }
 }
 
-pub struct BufferListRefMut<'a, T, U> {
-    buffers: &'a mut [(Box<[T]>, U)],
+pub struct BufferListRefMut<'a, T, U, A: Allocator = Global> {
+    buffers: &'a mut [Buffer<T, U, A>],
     start: usize,
     len: NonZeroUsize,
 }
 
-impl<'a, T, U> From<&'a mut BufferList<T, U>> for BufferListRefMut<'a, T, U> {
+impl<'a, T, U, A: Allocator> From<&'a mut BufferList<T, U, A>> for BufferListRefMut<'a, T, U, A> {
     #[inline]
-    fn from(value: &'a mut BufferList<T, U>) -> Self {
+    fn from(value: &'a mut BufferList<T, U, A>) -> Self {
         value.range_mut(0, value.buf_len).unwrap()
     }
 }
 
-impl<T, U> BufferListRefMut<'_, T, U> {
+impl<T, U, A: Allocator> BufferListRefMut<'_, T, U, A> {
     #[inline]
     pub fn len(&self) -> NonZeroUsize {
         self.len
@@ -120,8 +269,49 @@ impl<T, U> BufferListRefMut<'_, T, U> {
         })
     }
 
+    /// Like [`BufferList::range_mut`], with `start` relative to this view's start
+    #[inline]
+    pub fn range_mut(
+        &mut self,
+        start: usize,
+        len: NonZeroUsize,
+    ) -> Option<BufferListRefMut<T, U, A>> {
+        start
+            .checked_add(len.get())
+            .is_some_and(|end| end <= self.len.get())
+            .then(|| BufferListRefMut {
+                buffers: self.buffers,
+                start: self.start + start,
+                len,
+            })
+    }
+
+    /// Returns `None` if `src == dst` or either index is out of bounds
+    #[inline]
+    pub fn get_src_dst(&mut self, src: usize, dst: usize) -> Option<(&[T], &mut [T])> {
+        if src == dst || src.max(dst) >= self.buffers.len() {
+            return None;
+        }
+
+        let (src_buf, dst_buf) = if src < dst {
+            let (left, right) = self.buffers.split_at_mut(dst);
+            (&left[src].0, &mut right[0].0)
+        } else {
+            let (left, right) = self.buffers.split_at_mut(src);
+            (&right[0].0, &mut left[dst].0)
+        };
+
+        let range = self.start..self.start + self.len.get();
+        Some(unsafe {
+            (
+                src_buf.get_unchecked(range.clone()),
+                dst_buf.get_unchecked_mut(range),
+            )
+        })
+    }
+
     #[inline]
-    pub fn reborrow(&mut self) -> BufferListRefMut<T, U> {
+    pub fn reborrow(&mut self) -> BufferListRefMut<T, U, A> {
         BufferListRefMut {
             buffers: self.buffers,
             start: self.start,
@@ -130,38 +320,662 @@ impl<T, U> BufferListRefMut<'_, T, U> {
     }
 }
 
+impl<T: SimdElement, U, A: Allocator> BufferListRefMut<'_, T, U, A> {
+    /// Like [`BufferList::get_simd`], over this view's sample range
+    #[inline]
+    pub fn get_simd<const N: usize>(&self, index: usize) -> Option<&[Simd<T, N>]>
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        self.get(index).and_then(|(buf, _)| match buf.as_simd() {
+            ([], vectors, []) => Some(vectors),
+            _ => None,
+        })
+    }
+
+    /// Mutable version of [`Self::get_simd`]
+    #[inline]
+    pub fn get_simd_mut<const N: usize>(&mut self, index: usize) -> Option<&mut [Simd<T, N>]>
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        self.get_mut(index)
+            .and_then(|(buf, _)| match buf.as_simd_mut() {
+                ([], vectors, []) => Some(vectors),
+                _ => None,
+            })
+    }
+}
+
+/// Allocator aligning every allocation at least as strictly as `V`,
+/// used by [`BufferList::new_aligned_with`]
+pub struct AlignedAs<V>(PhantomData<V>);
+
+impl<V> AlignedAs<V> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+
+    #[inline]
+    fn layout(layout: Layout) -> Result<Layout, AllocError> {
+        layout
+            .align_to(mem::align_of::<V>())
+            .map_err(|_| AllocError)
+    }
+}
+
+impl<V> Default for AlignedAs<V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Clone for AlignedAs<V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for AlignedAs<V> {}
+
+unsafe impl<V> Allocator for AlignedAs<V> {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Global.allocate(Self::layout(layout)?)
+    }
+
+    #[inline]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Global.allocate_zeroed(Self::layout(layout)?)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // SAFETY: `layout` is the one passed to `allocate`, which succeeded with the
+        // aligned version of it, so it is valid, and it is the one `ptr` was allocated with
+        unsafe {
+            Global.deallocate(ptr, Self::layout(layout).unwrap_unchecked());
+        }
+    }
+}
+
 pub struct Buffers<'a, T: SimdFloat> {
     buffers: BufferListRefMut<'a, T, T::Bits>,
     inputs: &'a [usize],
     outputs: &'a [usize],
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GetBufferError {
     OOB,
     Empty,
 }
 
+impl<'a, T: SimdFloat> Buffers<'a, T> {
+    /// `inputs` and `outputs` map port indices to buffer indices in `buffers`,
+    /// `usize::MAX` marking a disconnected port.
+    ///
+    /// # Panics
+    ///
+    /// If a port index is neither `usize::MAX` nor a valid index into `buffers`
+    #[cfg(any(test, feature = "testing"))]
+    #[inline]
+    pub(crate) fn new(
+        buffers: BufferListRefMut<'a, T, T::Bits>,
+        inputs: &'a [usize],
+        outputs: &'a [usize],
+    ) -> Self {
+        let num_buffers = buffers.buffers.len();
+        assert!(
+            inputs
+                .iter()
+                .chain(outputs)
+                .all(|&i| i < num_buffers || i == usize::MAX),
+            "port mapped to a nonexistent buffer",
+        );
+
+        Self {
+            buffers,
+            inputs,
+            outputs,
+        }
+    }
+}
+
 impl<T: SimdFloat> Buffers<'_, T> {
+    /// The length of the buffers, in samples. Each element of a buffer holds one sample
+    /// of every lane (voice/channel), so any block size is representable, without padding.
     #[inline]
     pub fn len(&self) -> NonZeroUsize {
         self.buffers.len()
     }
 
     #[inline]
-    pub fn input(&self, index: usize) -> Result<(&[T], &T::Bits), GetBufferError> {
-        let &index = self.inputs.get(index).ok_or(GetBufferError::OOB)?;
+    pub fn reborrow(&mut self) -> Buffers<T> {
+        Buffers {
+            buffers: self.buffers.reborrow(),
+            inputs: self.inputs,
+            outputs: self.outputs,
+        }
+    }
+
+    /// A view where the inputs are this view's outputs, useful for processing
+    /// a previous processor's output in place
+    #[inline]
+    pub fn outputs_as_inputs(&mut self) -> Buffers<T> {
+        Buffers {
+            buffers: self.buffers.reborrow(),
+            inputs: self.outputs,
+            outputs: self.outputs,
+        }
+    }
+
+    /// A view over the given ranges of input and output ports, of the same buffers,
+    /// useful for running several processors side by side. Returns `None` if either
+    /// range is out of bounds.
+    #[inline]
+    pub fn ports(&mut self, inputs: Range<usize>, outputs: Range<usize>) -> Option<Buffers<T>> {
+        Some(Buffers {
+            inputs: self.inputs.get(inputs)?,
+            outputs: self.outputs.get(outputs)?,
+            buffers: self.buffers.reborrow(),
+        })
+    }
+
+    /// Whether the given input and output ports are connected to the same buffer
+    #[inline]
+    pub fn aliases(&self, input_idx: usize, output_idx: usize) -> bool {
+        Self::buffer_index(self.inputs, input_idx)
+            .ok()
+            .zip(Self::buffer_index(self.outputs, output_idx).ok())
+            .is_some_and(|(i, o)| i == o)
+    }
+
+    /// A view over the `[start, start + len)` sample range of the same buffers
+    #[inline]
+    pub fn sub_block(&mut self, start: usize, len: NonZeroUsize) -> Option<Buffers<T>> {
+        self.buffers.range_mut(start, len).map(|buffers| Buffers {
+            buffers,
+            inputs: self.inputs,
+            outputs: self.outputs,
+        })
+    }
+
+    /// Splits this block at each event's sample offset, and calls `f` on each sub-block,
+    /// along with the events occurring at its first sample.
+    ///
+    /// `events` must be sorted by offset, events past the end of the block are ignored.
+    #[inline]
+    pub fn for_each_subblock<E>(
+        &mut self,
+        mut events: &[(usize, E)],
+        mut f: impl FnMut(Buffers<T>, &[(usize, E)]),
+    ) {
+        let len = self.len().get();
+        let mut start = 0;
+
+        while start < len {
+            let num_current = events
+                .iter()
+                .take_while(|&&(offset, _)| offset <= start)
+                .count();
+            let (current, rest) = events.split_at(num_current);
+            let end = rest.first().map_or(len, |&(offset, _)| offset.min(len));
+
+            // SAFETY: end > start, because the offsets in rest are all > start
+            let sub_len = unsafe { NonZeroUsize::new_unchecked(end - start) };
+            f(self.sub_block(start, sub_len).unwrap(), current);
+
+            start = end;
+            events = rest;
+        }
+    }
+
+    #[inline]
+    fn buffer_index(ports: &[usize], index: usize) -> Result<usize, GetBufferError> {
+        let &index = ports.get(index).ok_or(GetBufferError::OOB)?;
         if index == usize::MAX {
             return Err(GetBufferError::Empty);
         }
+        Ok(index)
+    }
+
+    #[inline]
+    pub fn input(&self, index: usize) -> Result<(&[T], &T::Bits), GetBufferError> {
+        let index = Self::buffer_index(self.inputs, index)?;
         Ok(self.buffers.get(index).unwrap())
     }
 
     #[inline]
     pub fn output(&mut self, index: usize) -> Result<&mut [T], GetBufferError> {
-        let &index = self.outputs.get(index).ok_or(GetBufferError::OOB)?;
-        if index == usize::MAX {
-            return Err(GetBufferError::Empty);
-        }
+        let index = Self::buffer_index(self.outputs, index)?;
         Ok(self.buffers.get_mut(index).unwrap().0)
     }
+
+    #[inline]
+    pub fn fill_output(&mut self, index: usize, value: T) -> Result<(), GetBufferError> {
+        self.output(index)?.fill(value);
+        Ok(())
+    }
+
+    /// Copies the given input into the given output. Does nothing if they share the same buffer.
+    #[inline]
+    pub fn copy(&mut self, input_idx: usize, output_idx: usize) -> Result<(), GetBufferError> {
+        let src = Self::buffer_index(self.inputs, input_idx)?;
+        let dst = Self::buffer_index(self.outputs, output_idx)?;
+
+        if let Some((input, output)) = self.buffers.get_src_dst(src, dst) {
+            output.copy_from_slice(input);
+        }
+
+        Ok(())
+    }
+
+    /// Adds the given input, multiplied by `gain`, to the given output.
+    ///
+    /// If they share the same buffer, the input is read from the output's *current*
+    /// contents, so the output ends up multiplied by `1 + gain`. In particular, mixing
+    /// several inputs aliasing the same output doesn't sum them.
+    #[inline]
+    pub fn mix(
+        &mut self,
+        input_idx: usize,
+        output_idx: usize,
+        gain: T,
+    ) -> Result<(), GetBufferError>
+    where
+        T: Add<Output = T> + Mul<Output = T>,
+    {
+        let src = Self::buffer_index(self.inputs, input_idx)?;
+        let dst = Self::buffer_index(self.outputs, output_idx)?;
+
+        if let Some((input, output)) = self.buffers.get_src_dst(src, dst) {
+            for (out, &sample) in output.iter_mut().zip(input) {
+                *out = *out + sample * gain;
+            }
+        } else {
+            for sample in self.buffers.get_mut(dst).unwrap().0 {
+                *sample = *sample + *sample * gain;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[inline]
+fn assert_same_len(op: &str, dst_len: usize, src_len: usize) {
+    assert_eq!(
+        dst_len, src_len,
+        "{op}: destination and source buffers have different lengths",
+    );
+}
+
+// The following kernels operate on whole vectors, the vector width is chosen at compile
+// time, through `N`, like that of the processors whose buffers they operate on.
+
+/// `dst[i] = a[i] + b[i]`
+///
+/// # Panics
+///
+/// If the three buffers don't have the same length
+#[inline]
+pub fn sum_into<T: SimdElement, const N: usize>(
+    dst: &mut [Simd<T, N>],
+    a: &[Simd<T, N>],
+    b: &[Simd<T, N>],
+) where
+    LaneCount<N>: SupportedLaneCount,
+    Simd<T, N>: Add<Output = Simd<T, N>>,
+{
+    assert_same_len("sum_into", dst.len(), a.len());
+    assert_same_len("sum_into", dst.len(), b.len());
+    for ((out, &a), &b) in dst.iter_mut().zip(a).zip(b) {
+        *out = a + b;
+    }
+}
+
+/// `dst[i] += src[i]`
+///
+/// # Panics
+///
+/// If both buffers don't have the same length
+#[inline]
+pub fn add_assign<T: SimdElement, const N: usize>(dst: &mut [Simd<T, N>], src: &[Simd<T, N>])
+where
+    LaneCount<N>: SupportedLaneCount,
+    Simd<T, N>: AddAssign,
+{
+    assert_same_len("add_assign", dst.len(), src.len());
+    for (out, &sample) in dst.iter_mut().zip(src) {
+        *out += sample;
+    }
+}
+
+/// # Panics
+///
+/// If both buffers don't have the same length
+#[inline]
+pub fn copy<T: SimdElement, const N: usize>(dst: &mut [Simd<T, N>], src: &[Simd<T, N>])
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    assert_same_len("copy", dst.len(), src.len());
+    dst.copy_from_slice(src);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use core::array;
+    use simd_util::simd::f32x4;
+
+    const LEN: NonZeroUsize = NonZeroUsize::new(8).unwrap();
+
+    #[test]
+    #[should_panic = "nonexistent buffer"]
+    fn buffers_reject_out_of_range_ports() {
+        let mut list = BufferList::<f32x4, _>::new_vfloat_default(2, LEN);
+        let _ = Buffers::new((&mut list).into(), &[0, usize::MAX], &[2]);
+    }
+
+    #[test]
+    fn memory_requirements_match_allocations() {
+        let (_list, allocated) = test_alloc::allocated_bytes(|| {
+            BufferList::<f32x4, _>::try_new_vfloat_with(3, LEN, || 0u8).unwrap()
+        });
+
+        assert_eq!(
+            allocated,
+            BufferList::<f32x4, u8>::memory_requirements(3, LEN)
+        );
+    }
+
+    fn list_with(data: &[&[f32x4]]) -> BufferList<f32x4, <f32x4 as SimdFloat>::Bits> {
+        let mut list = BufferList::new_vfloat_default(data.len(), LEN);
+        for (i, data) in data.iter().enumerate() {
+            list.get_mut(i).unwrap().0.copy_from_slice(data);
+        }
+        list
+    }
+
+    fn ramp(offset: f32) -> [f32x4; LEN.get()] {
+        array::from_fn(|i| f32x4::splat(i as f32 + offset))
+    }
+
+    #[test]
+    fn copy_aliased_is_noop() {
+        let mut list = list_with(&[&ramp(1.)]);
+        Buffers::new((&mut list).into(), &[0], &[0])
+            .copy(0, 0)
+            .unwrap();
+        assert_eq!(list.get(0).unwrap().0, ramp(1.));
+    }
+
+    #[test]
+    fn copy_separate() {
+        let mut list = list_with(&[&ramp(1.), &ramp(5.)]);
+        Buffers::new((&mut list).into(), &[0], &[1])
+            .copy(0, 0)
+            .unwrap();
+        assert_eq!(list.get(1).unwrap().0, ramp(1.));
+    }
+
+    #[test]
+    fn mix_aliased_reads_current_output() {
+        let mut list = list_with(&[&ramp(1.)]);
+        Buffers::new((&mut list).into(), &[0], &[0])
+            .mix(0, 0, f32x4::splat(0.5))
+            .unwrap();
+        assert_eq!(
+            list.get(0).unwrap().0,
+            ramp(1.).map(|x| x * f32x4::splat(1.5))
+        );
+    }
+
+    #[test]
+    fn mix_separate() {
+        let mut list = list_with(&[&ramp(1.), &ramp(5.)]);
+        Buffers::new((&mut list).into(), &[0], &[1])
+            .mix(0, 0, f32x4::splat(0.5))
+            .unwrap();
+
+        let expected: [_; LEN.get()] =
+            array::from_fn(|i| ramp(5.)[i] + ramp(1.)[i] * f32x4::splat(0.5));
+        assert_eq!(list.get(1).unwrap().0, expected);
+    }
+
+    #[test]
+    fn copy_and_mix_disconnected() {
+        let mut list = list_with(&[&ramp(1.)]);
+        let mut buffers = Buffers::new((&mut list).into(), &[usize::MAX], &[0]);
+
+        assert_eq!(buffers.copy(0, 0), Err(GetBufferError::Empty));
+        assert_eq!(
+            buffers.mix(0, 0, f32x4::splat(1.)),
+            Err(GetBufferError::Empty)
+        );
+        assert_eq!(buffers.copy(0, 1), Err(GetBufferError::Empty));
+        assert_eq!(buffers.copy(1, 0), Err(GetBufferError::OOB));
+    }
+
+    /// (first sample, length, events) of each sub-block
+    fn subblocks(events: &[(usize, char)]) -> Vec<(f32, usize, Vec<char>)> {
+        let mut list = list_with(&[&ramp(0.)]);
+        let mut buffers = Buffers::new((&mut list).into(), &[0], &[]);
+        let mut blocks = Vec::new();
+
+        buffers.for_each_subblock(events, |sub, current| {
+            blocks.push((
+                sub.input(0).unwrap().0[0][0],
+                sub.len().get(),
+                current.iter().map(|&(_, e)| e).collect(),
+            ));
+        });
+
+        blocks
+    }
+
+    #[test]
+    fn subblocks_without_events() {
+        assert_eq!(subblocks(&[]), [(0., LEN.get(), vec![])]);
+    }
+
+    #[test]
+    fn subblocks_event_at_start() {
+        assert_eq!(
+            subblocks(&[(0, 'a'), (3, 'b')]),
+            [(0., 3, vec!['a']), (3., 5, vec!['b'])],
+        );
+    }
+
+    #[test]
+    fn subblocks_simultaneous_events() {
+        assert_eq!(
+            subblocks(&[(2, 'a'), (2, 'b'), (5, 'c'), (5, 'd'), (5, 'e')]),
+            [
+                (0., 2, vec![]),
+                (2., 3, vec!['a', 'b']),
+                (5., 3, vec!['c', 'd', 'e']),
+            ],
+        );
+    }
+
+    #[test]
+    fn subblocks_ignore_events_past_the_end() {
+        assert_eq!(
+            subblocks(&[(6, 'a'), (LEN.get(), 'b'), (LEN.get() + 3, 'c')]),
+            [(0., 6, vec![]), (6., 2, vec!['a'])],
+        );
+    }
+
+    #[test]
+    fn sub_block_rejects_overflowing_ranges() {
+        let mut list = list_with(&[&ramp(0.)]);
+        let mut buffers = Buffers::new((&mut list).into(), &[0], &[]);
+
+        assert!(buffers.sub_block(usize::MAX, NonZeroUsize::MIN).is_none());
+        assert!(buffers.sub_block(1, NonZeroUsize::MAX).is_none());
+        assert!(buffers.sub_block(LEN.get(), NonZeroUsize::MIN).is_none());
+
+        let mut sub = buffers.sub_block(2, NonZeroUsize::new(4).unwrap()).unwrap();
+        assert!(sub.sub_block(usize::MAX, NonZeroUsize::MIN).is_none());
+        assert!(sub.sub_block(3, NonZeroUsize::new(2).unwrap()).is_none());
+        assert_eq!(
+            sub.sub_block(3, NonZeroUsize::MIN)
+                .unwrap()
+                .input(0)
+                .unwrap()
+                .0,
+            [f32x4::splat(5.)],
+        );
+
+        assert!(list.range_mut(usize::MAX, NonZeroUsize::MIN).is_none());
+        assert!(list.range_mut(1, NonZeroUsize::MAX).is_none());
+    }
+
+    /// Buffers of `len` vectors of `N` lanes, with distinct, non-integer samples
+    fn kernel_inputs<const N: usize>(len: usize) -> [Vec<Simd<f32, N>>; 3]
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        array::from_fn(|buf| {
+            (0..len)
+                .map(|i| {
+                    Simd::from_array(array::from_fn(|lane| {
+                        (buf * 1000 + i * N + lane) as f32 * 0.37
+                    }))
+                })
+                .collect()
+        })
+    }
+
+    fn scalars<const N: usize>(buf: &[Simd<f32, N>]) -> Vec<f32>
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        buf.iter().flat_map(|v| v.to_array()).collect()
+    }
+
+    fn check_kernels<const N: usize>()
+    where
+        LaneCount<N>: SupportedLaneCount,
+    {
+        for len in [0, 1, 2, 3, 4, 5, 7, 9, 16, 17, 63] {
+            let [dst, a, b] = kernel_inputs::<N>(len);
+            let (sa, sb) = (scalars(&a), scalars(&b));
+
+            let mut out = dst.clone();
+            sum_into(&mut out, &a, &b);
+            let expected: Vec<_> = sa.iter().zip(&sb).map(|(a, b)| a + b).collect();
+            assert_eq!(scalars(&out), expected, "sum_into, {len} x {N}");
+
+            let mut out = dst.clone();
+            add_assign(&mut out, &a);
+            let expected: Vec<_> = scalars(&dst).iter().zip(&sa).map(|(d, a)| d + a).collect();
+            assert_eq!(scalars(&out), expected, "add_assign, {len} x {N}");
+
+            let mut out = dst.clone();
+            copy(&mut out, &a);
+            assert_eq!(scalars(&out), sa, "copy, {len} x {N}");
+        }
+    }
+
+    #[test]
+    fn kernels_match_scalar_loops() {
+        check_kernels::<1>();
+        check_kernels::<2>();
+        check_kernels::<4>();
+        check_kernels::<8>();
+    }
+
+    #[test]
+    #[should_panic = "add_assign: destination and source buffers have different lengths"]
+    fn kernels_reject_length_mismatch() {
+        let [mut dst, src, _] = kernel_inputs::<4>(5);
+        add_assign(&mut dst, &src[..4]);
+    }
+
+    fn scalar_list(num_buffers: usize, buf_len: usize) -> BufferList<f32, ()> {
+        // SAFETY: f32s are safely zeroable
+        unsafe { BufferList::new_default(num_buffers, NonZeroUsize::new(buf_len).unwrap()) }
+    }
+
+    #[test]
+    fn iterators_and_fill() {
+        let mut list = scalar_list(3, 5);
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter().len(), 3);
+        assert_eq!(list.iter_mut().len(), 3);
+        assert!(list.iter().all(|(buf, _)| buf.len() == 5));
+        assert!(scalar_list(0, 5).iter().next().is_none());
+
+        list.fill(0.5);
+        assert!(list.iter().all(|(buf, _)| buf == [0.5; 5]));
+    }
+
+    fn aligned_list(num_buffers: usize, buf_len: usize) -> BufferList<f32, (), AlignedAs<f32x4>> {
+        let mut list =
+            BufferList::new_aligned_with(num_buffers, NonZeroUsize::new(buf_len).unwrap(), || ());
+        for (i, (buf, _)) in list.iter_mut().enumerate() {
+            for (j, sample) in buf.iter_mut().enumerate() {
+                *sample = (i * buf_len + j) as f32;
+            }
+        }
+        list
+    }
+
+    #[test]
+    fn simd_views() {
+        let mut list = aligned_list(2, 8);
+
+        assert_eq!(
+            list.get_simd::<4>(0).unwrap(),
+            [[0., 1., 2., 3.], [4., 5., 6., 7.]].map(f32x4::from_array)
+        );
+
+        let vectors = list.get_simd_mut::<4>(1).unwrap();
+        assert_eq!(
+            vectors,
+            [[8., 9., 10., 11.], [12., 13., 14., 15.]].map(f32x4::from_array)
+        );
+        vectors[1] = f32x4::splat(0.);
+        assert_eq!(list.get(1).unwrap().0[4..], [0.; 4]);
+
+        assert!(list.get_simd::<4>(2).is_none());
+        assert!(list.get_simd_mut::<4>(2).is_none());
+    }
+
+    #[test]
+    fn simd_views_of_sub_ranges() {
+        let mut list = aligned_list(1, 12);
+
+        let mut view = list.range_mut(4, NonZeroUsize::new(8).unwrap()).unwrap();
+        assert_eq!(
+            view.get_simd_mut::<4>(0).unwrap(),
+            [[4., 5., 6., 7.], [8., 9., 10., 11.]].map(f32x4::from_array)
+        );
+
+        // starting one sample past an aligned address, the view is misaligned
+        let view = list.range_mut(1, NonZeroUsize::new(8).unwrap()).unwrap();
+        assert!(view.get_simd::<4>(0).is_none());
+        assert!(view.get_simd::<2>(0).is_none());
+    }
+
+    #[test]
+    fn simd_views_reject_partial_vectors() {
+        let mut list = aligned_list(1, 6);
+        assert!(list.get_simd::<4>(0).is_none());
+        assert!(list.get_simd_mut::<4>(0).is_none());
+        assert!(list.get_simd::<8>(0).is_none());
+        assert!(list.get_simd::<2>(0).is_some());
+
+        let mut view = list.range_mut(0, NonZeroUsize::new(5).unwrap()).unwrap();
+        assert!(view.get_simd_mut::<4>(0).is_none());
+    }
 }
@@ -0,0 +1,133 @@
+use core::fmt;
+
+/// Compile-time configuration of this build, with a [`Display`](fmt::Display)
+/// implementation meant to be pasted into bug reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub version: &'static str,
+    pub target_arch: &'static str,
+    /// Number of `f32` lanes in `simd_util`'s native vector type, for the enabled target
+    /// features (2 on targets without SIMD). Processors are generic over their lane count,
+    /// so this is the width they can use without splitting vectors, not one they're bound to.
+    pub vector_width: usize,
+    /// Cargo features of this crate, and whether they are enabled
+    pub features: &'static [(&'static str, bool)],
+    /// SIMD-relevant target features, and whether this build was compiled with them
+    pub target_features: &'static [(&'static str, bool)],
+}
+
+macro_rules! flags {
+    ($attr:ident: $($name:literal),* $(,)?) => {
+        &[$(($name, cfg!($attr = $name))),*]
+    };
+}
+
+#[inline]
+pub const fn capabilities() -> Capabilities {
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        target_arch: if cfg!(target_arch = "x86_64") {
+            "x86_64"
+        } else if cfg!(target_arch = "x86") {
+            "x86"
+        } else if cfg!(target_arch = "aarch64") {
+            "aarch64"
+        } else if cfg!(target_arch = "arm") {
+            "arm"
+        } else if cfg!(target_arch = "wasm32") {
+            "wasm32"
+        } else {
+            "other"
+        },
+        vector_width: simd_util::FLOATS_PER_VECTOR,
+        features: flags!(feature: "std", "std_simd", "core_simd_crate", "testing"),
+        target_features: flags!(
+            target_feature: "sse2",
+            "sse4.1",
+            "avx",
+            "avx2",
+            "fma",
+            "avx512f",
+            "neon",
+            "simd128",
+        ),
+    }
+}
+
+fn write_enabled(f: &mut fmt::Formatter, flags: &[(&str, bool)]) -> fmt::Result {
+    let mut enabled = flags.iter().filter_map(|&(name, on)| on.then_some(name));
+
+    match enabled.next() {
+        None => f.write_str("none"),
+        Some(first) => {
+            f.write_str(first)?;
+            enabled.try_for_each(|name| write!(f, ", {name}"))
+        }
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "mythril {} ({})", self.version, self.target_arch)?;
+        writeln!(f, "vector width: {} x f32", self.vector_width)?;
+        f.write_str("features: ")?;
+        write_enabled(f, self.features)?;
+        f.write_str("\ntarget features: ")?;
+        write_enabled(f, self.target_features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn enabled(flags: &[(&str, bool)], name: &str) -> bool {
+        flags
+            .iter()
+            .find(|&&(flag, _)| flag == name)
+            .unwrap_or_else(|| panic!("{name} isn't reported"))
+            .1
+    }
+
+    #[test]
+    fn matches_compile_time_configuration() {
+        let caps = capabilities();
+
+        assert_eq!(caps.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(enabled(caps.features, "std"), cfg!(feature = "std"));
+        assert_eq!(enabled(caps.features, "testing"), cfg!(feature = "testing"));
+        assert_eq!(
+            enabled(caps.target_features, "avx2"),
+            cfg!(target_feature = "avx2"),
+        );
+        assert_eq!(
+            enabled(caps.target_features, "neon"),
+            cfg!(target_feature = "neon"),
+        );
+
+        assert_eq!(caps.vector_width, simd_util::FLOATS_PER_VECTOR);
+        assert!(caps.vector_width.is_power_of_two() && caps.vector_width >= 2);
+
+        if cfg!(target_arch = "x86_64") {
+            assert_eq!(caps.target_arch, "x86_64");
+            // SSE2 is part of the x86_64 baseline
+            assert!(caps.vector_width >= 4);
+        }
+    }
+
+    #[test]
+    fn display() {
+        let report = capabilities().to_string();
+        let mut lines = report.lines();
+
+        assert!(lines.next().unwrap().starts_with("mythril "));
+        assert_eq!(
+            lines.next().unwrap(),
+            alloc::format!("vector width: {} x f32", simd_util::FLOATS_PER_VECTOR),
+        );
+        assert!(lines.next().unwrap().starts_with("features: "));
+        assert!(lines.next().unwrap().starts_with("target features: "));
+        assert!(lines.next().is_none());
+    }
+}
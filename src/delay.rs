@@ -1,19 +1,33 @@
 use super::*;
-use core::{marker::PhantomData, ptr::NonNull};
+use core::{array, marker::PhantomData, ptr::NonNull, slice};
+use simd_util::simd::{
+    cmp::SimdPartialOrd,
+    num::{SimdFloat, SimdUint},
+    LaneCount, Mask, Simd, SupportedLaneCount,
+};
 
-/// A delay buffer with a fixed, non-zero size
-#[derive(Clone, Debug)]
+/// A delay buffer with a non-zero size, that can be changed without reallocating,
+/// within the capacity it was created with
+#[derive(Debug)]
 pub struct Delay<T> {
     start: NonNull<T>,
     end: NonNull<T>,
     current: NonNull<T>,
+    capacity_end: NonNull<T>,
     _marker: PhantomData<T>,
 }
 
 impl<T: Default> Delay<T> {
     #[inline]
     pub fn new(num_samples: NonZeroUsize) -> Self {
-        let len = num_samples.get();
+        Self::with_capacity(num_samples)
+    }
+
+    /// Creates a delay of length `max`, which can later be shortened
+    /// (and lengthened back up to `max`) using [`Self::set_len`]
+    #[inline]
+    pub fn with_capacity(max: NonZeroUsize) -> Self {
+        let len = max.get();
         let start =
             Box::into_non_null(Box::from_iter(iter::repeat_with(T::default).take(len))).cast();
         let end = unsafe { start.add(len) };
@@ -22,18 +36,64 @@ impl<T: Default> Delay<T> {
             start,
             end,
             current: start,
+            capacity_end: end,
             _marker: PhantomData,
         }
     }
+
+    /// Resets all samples to `T::default()`, without reallocating
+    #[inline]
+    pub fn clear(&mut self) {
+        self.as_mut_allocation().fill_with(T::default);
+        self.current = self.start;
+    }
+
+    /// Changes the length of the delay, without reallocating. The most recent
+    /// `len` samples are preserved, in order, and, when lengthening, the older
+    /// ones are reset to `T::default()`. Thus, subsequent output is the same as that
+    /// of a new delay of length `len`, fed the same history.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is greater than `self.capacity()`
+    #[inline]
+    pub fn set_len(&mut self, len: NonZeroUsize) {
+        let new_len = len.get();
+        assert!(
+            new_len <= self.capacity().get(),
+            "delay length ({new_len}) exceeds capacity ({})",
+            self.capacity(),
+        );
+
+        let old_len = self.len().get();
+        let current = self.current_index();
+        let allocation = self.as_mut_allocation();
+
+        // put samples in chronological order, the oldest one at index 0
+        allocation[..old_len].rotate_left(current);
+
+        if new_len < old_len {
+            allocation[..old_len].rotate_left(old_len - new_len);
+        } else {
+            let num_new = new_len - old_len;
+            allocation[..new_len].rotate_right(num_new);
+            allocation[..num_new].fill_with(T::default);
+        }
+
+        // SAFETY: new_len <= capacity
+        self.end = unsafe { self.start.add(new_len) };
+        self.current = self.start;
+    }
 }
 
 impl<T> Delay<T> {
+    /// Returns the whole allocation, and the index of the current sample.
+    /// If the delay has been shortened, the elements past its length are stale.
     #[inline]
     pub fn into_boxed_slice(self) -> (Box<[T]>, usize) {
-        (
-            unsafe { Box::from_non_null(self.as_slice().into()) },
-            self.current_index(),
-        )
+        let this = mem::ManuallyDrop::new(self);
+        let ptr = NonNull::slice_from_raw_parts(this.start, this.capacity().get());
+        (unsafe { Box::from_non_null(ptr) }, this.current_index())
     }
 
     #[inline]
@@ -49,6 +109,12 @@ impl<T> Delay<T> {
         unsafe { NonZeroUsize::new_unchecked(self.end.sub_ptr(self.start)) }
     }
 
+    #[inline]
+    pub fn capacity(&self) -> NonZeroUsize {
+        // SAFETY: self.capacity_end >= self.end
+        unsafe { NonZeroUsize::new_unchecked(self.capacity_end.sub_ptr(self.start)) }
+    }
+
     #[inline]
     pub fn as_slice(&self) -> &[T] {
         // SAFETY: see above
@@ -56,12 +122,54 @@ impl<T> Delay<T> {
         unsafe { ptr.as_ref() }
     }
 
+    #[inline]
+    fn as_allocation(&self) -> &[T] {
+        // SAFETY: self.start and self.capacity_end are both edges of the boxed slice
+        let ptr = NonNull::slice_from_raw_parts(self.start, self.capacity().get());
+        unsafe { ptr.as_ref() }
+    }
+
+    #[inline]
+    fn as_mut_allocation(&mut self) -> &mut [T] {
+        // SAFETY: self.start and self.capacity_end are both edges of the boxed slice
+        let mut ptr = NonNull::slice_from_raw_parts(self.start, self.capacity().get());
+        unsafe { ptr.as_mut() }
+    }
+
     #[inline]
     pub fn current_index(&self) -> usize {
         // SAFETY: self.current is always >= self.start
         unsafe { self.current.sub_ptr(self.start) }
     }
 
+    /// Returns the sample that was pushed `n` samples ago, if `n` is in `1..=self.len()`
+    #[inline]
+    pub fn get_delayed(&self, n: usize) -> Option<&T> {
+        let len = self.len().get();
+        (1..=len)
+            .contains(&n)
+            .then(|| &self.as_slice()[(self.current_index() + len - n) % len])
+    }
+
+    /// Copies the buffered samples into `out`, in the order in which they would be
+    /// output, without changing the state of the delay. Returns the number of
+    /// samples copied, which is the minimum of `out.len()` and `self.len()`.
+    #[inline]
+    pub fn drain_tail(&self, out: &mut [T]) -> usize
+    where
+        T: Clone,
+    {
+        let (newest, oldest) = self.as_slice().split_at(self.current_index());
+        let mut num_copied = 0;
+
+        for (out, sample) in out.iter_mut().zip(oldest.iter().chain(newest)) {
+            out.clone_from(sample);
+            num_copied += 1;
+        }
+
+        num_copied
+    }
+
     #[inline]
     pub fn process_sample_in_place(&mut self, sample: &mut T) {
         // SAFETY: same as `Self::get_current`
@@ -88,8 +196,243 @@ impl<T> Delay<T> {
     }
 }
 
+impl<T: Clone> Clone for Delay<T> {
+    /// Copies the whole allocation, the clone thus has the same capacity
+    #[inline]
+    fn clone(&self) -> Self {
+        let start = Box::into_non_null(Box::<[T]>::from(self.as_allocation())).cast();
+
+        // SAFETY: the new allocation has the same capacity as self's
+        unsafe {
+            Self {
+                start,
+                end: start.add(self.len().get()),
+                current: start.add(self.current_index()),
+                capacity_end: start.add(self.capacity().get()),
+                _marker: PhantomData,
+            }
+        }
+    }
+}
+
 impl<T> Drop for Delay<T> {
     fn drop(&mut self) {
-        let _b = unsafe { Box::from_non_null(self.as_slice().into()) };
+        let _b = unsafe { Box::from_non_null(self.as_mut_allocation().into()) };
+    }
+}
+
+/// A delay line with a per-lane, fractional (linearly interpolated) delay time
+pub struct FractionalDelay<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    delay: Delay<Simd<f32, N>>,
+    delay_samples: Simd<f32, N>,
+}
+
+impl<const N: usize> FractionalDelay<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    #[inline]
+    pub fn new(max_delay_samples: usize) -> Self {
+        // one extra sample for the zero delay case, and another for interpolation
+        Self {
+            delay: Delay::new(NonZeroUsize::new(max_delay_samples + 2).unwrap()),
+            delay_samples: Simd::splat(0.),
+        }
+    }
+
+    #[inline]
+    pub fn max_delay_samples(&self) -> usize {
+        self.delay.len().get() - 2
+    }
+
+    #[inline]
+    pub fn delay_samples(&self) -> Simd<f32, N> {
+        self.delay_samples
+    }
+
+    /// Values are clamped to `[0, self.max_delay_samples()]`
+    #[inline]
+    pub fn set_delay_samples(&mut self, samples: Simd<f32, N>) {
+        self.delay_samples = samples.simd_clamp(
+            Simd::splat(0.),
+            Simd::splat(self.max_delay_samples() as f32),
+        );
+    }
+
+    #[inline]
+    pub fn process(&mut self, sample: Simd<f32, N>) -> Simd<f32, N> {
+        self.delay.process_sample(sample);
+
+        let len = Simd::splat(self.delay.len().get());
+        // delay_samples is in [0, max_delay_samples], see Self::set_delay_samples
+        let n = self.delay_samples.cast::<usize>();
+        let frac = self.delay_samples - n.cast();
+
+        // n + 1 and n + 2 are in [1, len], so the read positions are in [0, 2 * len),
+        // and a single conditional subtraction wraps them back into the buffer
+        let pos = Simd::splat(self.delay.current_index()) + len - n;
+        let wrap = |pos: Simd<usize, N>| pos.simd_ge(len).select(pos - len, pos);
+        let lanes = Simd::from_array(array::from_fn(|i| i));
+
+        let samples = self.delay.as_slice();
+        // SAFETY: Simd<f32, N> has the same size as, and a stricter alignment than, [f32; N]
+        let flat =
+            unsafe { slice::from_raw_parts(samples.as_ptr().cast::<f32>(), samples.len() * N) };
+
+        let gather = |pos: Simd<usize, N>| {
+            // SAFETY: wrap(pos) is in [0, len) (see above) and lane < N, so every
+            // index is less than len * N == flat.len()
+            unsafe {
+                Simd::gather_select_unchecked(
+                    flat,
+                    Mask::splat(true),
+                    wrap(pos) * Simd::splat(N) + lanes,
+                    Simd::splat(0.),
+                )
+            }
+        };
+
+        let a = gather(pos - Simd::splat(1));
+        let b = gather(pos - Simd::splat(2));
+        a + (b - a) * frac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use simd_util::simd::f32x4;
+
+    #[test]
+    fn fractional_delay_group_delay() {
+        let delays = f32x4::from_array([0., 0.25, 2.5, 6.75]);
+        let mut delay = FractionalDelay::new(8);
+        delay.set_delay_samples(delays);
+
+        let response: Vec<_> = iter::once(f32x4::splat(1.))
+            .chain(iter::repeat(f32x4::splat(0.)))
+            .take(12)
+            .map(|sample| delay.process(sample))
+            .collect();
+
+        let gain: f32x4 = response.iter().sum();
+        let centroid: f32x4 = response
+            .iter()
+            .enumerate()
+            .map(|(i, &h)| h * f32x4::splat(i as f32))
+            .sum();
+
+        assert_eq!(gain, f32x4::splat(1.));
+        assert_eq!(centroid / gain, delays);
+    }
+
+    #[test]
+    fn fractional_delay_wraps_around() {
+        let delays = f32x4::from_array([0., 1.5, 3.25, 8.]);
+        let mut delay = FractionalDelay::new(8);
+        delay.set_delay_samples(delays);
+
+        // a ramp, read back several times around the buffer
+        for t in 0..40 {
+            let output = delay.process(f32x4::splat(t as f32));
+            if t >= 9 {
+                assert_eq!(output, f32x4::splat(t as f32) - delays);
+            }
+        }
+    }
+
+    fn nz(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    fn fed(len: usize, history: &[i32]) -> Delay<i32> {
+        let mut delay = Delay::new(nz(len));
+        delay.process_buffer(&mut history.to_vec());
+        delay
+    }
+
+    /// Checks that both delays output the same samples from now on
+    fn assert_same_output(mut a: Delay<i32>, mut b: Delay<i32>) {
+        let mut tail_a = vec![0; a.len().get()];
+        let mut tail_b = vec![0; b.len().get()];
+        a.drain_tail(&mut tail_a);
+        b.drain_tail(&mut tail_b);
+        assert_eq!(tail_a, tail_b);
+
+        let mut out_a: Vec<_> = (100..120).collect();
+        let mut out_b = out_a.clone();
+        a.process_buffer(&mut out_a);
+        b.process_buffer(&mut out_b);
+        assert_eq!(out_a, out_b);
+    }
+
+    #[test]
+    fn shrink_with_current_past_new_end() {
+        let history: Vec<_> = (1..=13).collect();
+        let mut delay = fed(8, &history);
+        assert_eq!(delay.current_index(), 5);
+
+        delay.set_len(nz(3));
+        assert_eq!(delay.len().get(), 3);
+        assert_eq!(delay.capacity().get(), 8);
+
+        assert_same_output(delay, fed(3, &history));
+    }
+
+    #[test]
+    fn shrink_then_grow_within_capacity() {
+        let history: Vec<_> = (1..=11).collect();
+        let mut delay = Delay::with_capacity(nz(8));
+        delay.set_len(nz(4));
+        delay.process_buffer(&mut history.clone());
+
+        // only the last 4 samples are kept, older ones are reset
+        delay.set_len(nz(7));
+        assert_same_output(delay, fed(7, &history[history.len() - 4..]));
+    }
+
+    #[test]
+    #[should_panic = "exceeds capacity"]
+    fn grow_past_capacity() {
+        Delay::<i32>::new(nz(4)).set_len(nz(5));
+    }
+
+    #[test]
+    fn drain_tail_order() {
+        let delay = fed(4, &[1, 2, 3, 4, 5, 6]);
+
+        let mut tail = [0; 4];
+        assert_eq!(delay.drain_tail(&mut tail), 4);
+        assert_eq!(tail, [3, 4, 5, 6]);
+
+        let mut short = [0; 3];
+        assert_eq!(delay.drain_tail(&mut short), 3);
+        assert_eq!(short, [3, 4, 5]);
+
+        let mut long = [0; 6];
+        assert_eq!(delay.drain_tail(&mut long), 4);
+        assert_eq!(long, [3, 4, 5, 6, 0, 0]);
+
+        // the state is left untouched
+        assert_same_output(delay, fed(4, &[3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let mut delay = Delay::with_capacity(nz(6));
+        delay.set_len(nz(4));
+        delay.process_buffer(&mut [1, 2, 3, 4, 5]);
+
+        let mut clone = delay.clone();
+        assert_eq!(clone.capacity(), delay.capacity());
+        assert_eq!(clone.process_sample(7), 2);
+
+        assert_same_output(delay, fed(4, &[2, 3, 4, 5]));
+        // still resizable up to the original capacity
+        clone.set_len(nz(6));
     }
 }
@@ -1,17 +1,44 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![feature(
     portable_simd,
     new_zeroed_alloc,
     slice_from_ptr_range,
     ptr_sub_ptr,
-    box_vec_non_null
+    box_vec_non_null,
+    allocator_api
 )]
 
 extern crate alloc;
 
 pub mod buffer;
+pub mod capabilities;
 pub mod delay;
 pub mod lender;
 pub mod processor;
+pub mod processors;
+#[cfg(test)]
+mod test_alloc;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
-use alloc::sync::Arc;
-use core::{iter, mem, num::NonZeroUsize};
+pub use capabilities::{capabilities, Capabilities};
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{alloc::AllocError, iter, mem, num::NonZeroUsize};
+
+/// Fallible version of collecting `len` results of `f` into a boxed slice,
+/// allocating exactly once
+#[inline]
+pub(crate) fn try_boxed_slice<T>(
+    len: usize,
+    mut f: impl FnMut() -> Result<T, AllocError>,
+) -> Result<Box<[T]>, AllocError> {
+    let mut vec = Vec::new();
+    vec.try_reserve_exact(len).map_err(|_| AllocError)?;
+
+    for _ in 0..len {
+        vec.push(f()?);
+    }
+
+    Ok(vec.into_boxed_slice())
+}
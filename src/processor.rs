@@ -1,23 +1,254 @@
 use super::*;
 use buffer::Buffers;
+use core::{
+    alloc::AllocError,
+    sync::atomic::{AtomicU32, Ordering},
+};
+#[cfg(feature = "std")]
+use simd_util::simd::f32x2;
 use simd_util::simd::num::SimdFloat;
-use std::io::{Read, Write};
+#[cfg(feature = "std")]
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Read, Write},
+};
 
+/// Shared parameter state of a processor.
+///
+/// Both methods have failing default implementations, so that implementors that
+/// can't save their state compile whether or not the `std` feature is enabled.
 pub trait Parameters {
-    fn serialize(&self, writer: &mut dyn Write);
-    fn deserialize(&self, reader: &mut dyn Read);
+    /// The default implementation fails with [`io::ErrorKind::Unsupported`]
+    #[cfg(feature = "std")]
+    #[inline]
+    fn serialize(&self, _writer: &mut dyn Write) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// The default implementation fails with [`DeserializeError::Unsupported`]
+    #[cfg(feature = "std")]
+    #[inline]
+    fn deserialize(&self, _reader: &mut dyn Read) -> Result<(), DeserializeError> {
+        Err(DeserializeError::Unsupported)
+    }
 }
 
 impl Parameters for () {
+    #[cfg(feature = "std")]
+    #[inline]
+    fn serialize(&self, _writer: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+    #[cfg(feature = "std")]
+    #[inline]
+    fn deserialize(&self, _reader: &mut dyn Read) -> Result<(), DeserializeError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum DeserializeError {
+    /// The input ended before the end of the parameter block
+    Truncated,
+    VersionMismatch {
+        expected: u8,
+        found: u8,
+    },
+    /// These parameters can't be deserialized
+    Unsupported,
+    Io(io::Error),
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for DeserializeError {
     #[inline]
-    fn serialize(&self, _writer: &mut dyn Write) {}
+    fn from(value: io::Error) -> Self {
+        if value.kind() == io::ErrorKind::UnexpectedEof {
+            Self::Truncated
+        } else {
+            Self::Io(value)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated parameter data"),
+            Self::VersionMismatch { expected, found } => write!(
+                f,
+                "parameter format version mismatch (expected {expected}, found {found})"
+            ),
+            Self::Unsupported => f.write_str("parameters don't support deserialization"),
+            Self::Io(e) => write!(f, "io error while reading parameters: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for DeserializeError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Encoder/decoder for a simple parameter state format, meant to be used
+/// in [`Parameters`] implementations.
+///
+/// The layout is a version byte, followed by the number of parameters, as a
+/// little-endian `u32`, followed by that many (little-endian `u64` id, 2 little-endian
+/// `f32` values) pairs.
+#[cfg(feature = "std")]
+pub struct ParamBlock;
+
+#[cfg(feature = "std")]
+impl ParamBlock {
+    pub const VERSION: u8 = 1;
+
+    pub fn write(
+        writer: &mut dyn Write,
+        params: impl ExactSizeIterator<Item = (u64, f32x2)>,
+    ) -> io::Result<()> {
+        let len = u32::try_from(params.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many parameters"))?;
+
+        writer.write_all(&[Self::VERSION])?;
+        writer.write_all(&len.to_le_bytes())?;
+
+        for (id, value) in params {
+            let [l, r] = value.to_array();
+            writer.write_all(&id.to_le_bytes())?;
+            writer.write_all(&l.to_le_bytes())?;
+            writer.write_all(&r.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Calls `f` on every (id, value) pair in the block, in order. Implementors should
+    /// skip ids they don't know about, for compatibility with states saved by newer versions.
+    pub fn read(
+        reader: &mut dyn Read,
+        mut f: impl FnMut(u64, f32x2),
+    ) -> Result<(), DeserializeError> {
+        let mut version = [0];
+        reader.read_exact(&mut version)?;
+        let [found] = version;
+
+        if found != Self::VERSION {
+            return Err(DeserializeError::VersionMismatch {
+                expected: Self::VERSION,
+                found,
+            });
+        }
+
+        let mut len = [0; 4];
+        reader.read_exact(&mut len)?;
+
+        for _ in 0..u32::from_le_bytes(len) {
+            let mut id = [0; 8];
+            let mut l = [0; 4];
+            let mut r = [0; 4];
+            reader.read_exact(&mut id)?;
+            reader.read_exact(&mut l)?;
+            reader.read_exact(&mut r)?;
+
+            f(
+                u64::from_le_bytes(id),
+                f32x2::from_array([f32::from_le_bytes(l), f32::from_le_bytes(r)]),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// A fixed set of `f32` parameters, that can be read and written from any thread,
+/// identified by their index. Meant to be used in [`Parameters`] implementations,
+/// which it can (de)serialize as a [`ParamBlock`].
+#[derive(Debug)]
+pub struct ParamArray<const LEN: usize> {
+    values: [AtomicU32; LEN],
+}
+
+impl<const LEN: usize> ParamArray<LEN> {
+    #[inline]
+    pub fn new(values: [f32; LEN]) -> Self {
+        Self {
+            values: values.map(|v| AtomicU32::new(v.to_bits())),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, id: u64) -> Option<f32> {
+        let value = self.values.get(usize::try_from(id).ok()?)?;
+        Some(f32::from_bits(value.load(Ordering::Relaxed)))
+    }
+
+    /// Returns `false`, and does nothing, if `id` doesn't exist
+    #[inline]
+    pub fn set(&self, id: u64, value: f32) -> bool {
+        usize::try_from(id)
+            .ok()
+            .and_then(|i| self.values.get(i))
+            .map(|v| v.store(value.to_bits(), Ordering::Relaxed))
+            .is_some()
+    }
+
+    /// Writes every parameter, in order, as a [`ParamBlock`]
+    #[cfg(feature = "std")]
+    pub fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        ParamBlock::write(
+            writer,
+            self.values.iter().enumerate().map(|(id, value)| {
+                let value = f32::from_bits(value.load(Ordering::Relaxed));
+                (id as u64, f32x2::splat(value))
+            }),
+        )
+    }
+
+    /// Reads a [`ParamBlock`], ignoring unknown ids
+    #[cfg(feature = "std")]
+    pub fn deserialize(&self, reader: &mut dyn Read) -> Result<(), DeserializeError> {
+        ParamBlock::read(reader, |id, value| {
+            self.set(id, value[0]);
+        })
+    }
+}
+
+/// Heap memory a processor needs to be initialized, as reported by
+/// [`Processor::memory_requirements`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryReport {
+    #[default]
+    Unknown,
+    Bytes(usize),
+}
+
+impl MemoryReport {
+    /// Sums two reports, yielding `Unknown` if either of them is
     #[inline]
-    fn deserialize(&self, _reader: &mut dyn Read) {}
+    pub fn combine(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Bytes(a), Self::Bytes(b)) => Self::Bytes(a + b),
+            _ => Self::Unknown,
+        }
+    }
 }
 
 pub trait Processor {
     type Sample: SimdFloat;
 
+    /// Number of (inputs, outputs)
+    fn audio_io_layout(&self) -> (usize, usize);
+
     fn process(
         &mut self,
         buffers: Buffers<Self::Sample>,
@@ -28,12 +259,42 @@ pub trait Processor {
 
     fn initialize(&mut self, sr: f32, max_buffer_size: usize, max_num_clusters: usize) -> usize;
 
+    /// Fallible version of [`Self::initialize`], for targets where running out of memory
+    /// must not abort.
+    ///
+    /// The default implementation calls [`Self::initialize`], and thus, never fails.
+    #[inline]
+    fn try_initialize(
+        &mut self,
+        sr: f32,
+        max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> Result<usize, AllocError> {
+        Ok(self.initialize(sr, max_buffer_size, max_num_clusters))
+    }
+
+    /// How much heap memory [`Self::initialize`] would allocate with these arguments.
+    #[inline]
+    fn memory_requirements(
+        &self,
+        _sr: f32,
+        _max_buffer_size: usize,
+        _max_num_clusters: usize,
+    ) -> MemoryReport {
+        MemoryReport::Unknown
+    }
+
     fn reset(&mut self, index: (usize, usize));
 }
 
 impl<T: ?Sized + Processor> Processor for Box<T> {
     type Sample = T::Sample;
 
+    #[inline]
+    fn audio_io_layout(&self) -> (usize, usize) {
+        self.as_ref().audio_io_layout()
+    }
+
     #[inline]
     fn process(
         &mut self,
@@ -49,6 +310,28 @@ impl<T: ?Sized + Processor> Processor for Box<T> {
             .initialize(sr, max_buffer_size, max_num_clusters)
     }
 
+    #[inline]
+    fn try_initialize(
+        &mut self,
+        sr: f32,
+        max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> Result<usize, AllocError> {
+        self.as_mut()
+            .try_initialize(sr, max_buffer_size, max_num_clusters)
+    }
+
+    #[inline]
+    fn memory_requirements(
+        &self,
+        sr: f32,
+        max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> MemoryReport {
+        self.as_ref()
+            .memory_requirements(sr, max_buffer_size, max_num_clusters)
+    }
+
     #[inline]
     fn parameters(&self) -> Arc<dyn Parameters> {
         self.as_ref().parameters()
@@ -59,3 +342,96 @@ impl<T: ?Sized + Processor> Processor for Box<T> {
         self.as_mut().reset(index);
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn block(params: &[(u64, f32x2)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ParamBlock::write(&mut bytes, params.iter().copied()).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn unknown_ids_are_skipped() {
+        let bytes = block(&[
+            (0, f32x2::splat(0.5)),
+            (42, f32x2::splat(7.)),
+            (1, f32x2::from_array([0.25, 0.75])),
+        ]);
+
+        let mut known = Vec::new();
+        ParamBlock::read(&mut bytes.as_slice(), |id, value| {
+            if id < 2 {
+                known.push((id, value));
+            }
+        })
+        .unwrap();
+
+        assert_eq!(
+            known,
+            [(0, f32x2::splat(0.5)), (1, f32x2::from_array([0.25, 0.75]))],
+        );
+    }
+
+    #[test]
+    fn truncated_input() {
+        let bytes = block(&[(0, f32x2::splat(1.)), (1, f32x2::splat(2.))]);
+
+        for len in 0..bytes.len() {
+            let result = ParamBlock::read(&mut &bytes[..len], |_, _| {});
+            assert!(
+                matches!(result, Err(DeserializeError::Truncated)),
+                "{len} bytes",
+            );
+        }
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let mut bytes = block(&[(0, f32x2::splat(1.))]);
+        bytes[0] = ParamBlock::VERSION + 1;
+
+        let mut called = false;
+        let result = ParamBlock::read(&mut bytes.as_slice(), |_, _| called = true);
+
+        assert!(matches!(
+            result,
+            Err(DeserializeError::VersionMismatch { expected, found })
+                if expected == ParamBlock::VERSION && found == ParamBlock::VERSION + 1
+        ));
+        assert!(!called);
+    }
+
+    #[test]
+    fn param_array_round_trip() {
+        let params = ParamArray::new([0.5, 1., 2.]);
+        assert!(params.set(1, -1.));
+        assert!(!params.set(3, 4.));
+        assert!(!params.set(u64::MAX, 4.));
+        assert_eq!(params.get(3), None);
+
+        let mut bytes = Vec::new();
+        params.serialize(&mut bytes).unwrap();
+
+        let restored = ParamArray::new([0.; 2]);
+        restored.deserialize(&mut bytes.as_slice()).unwrap();
+        assert_eq!([restored.get(0), restored.get(1)], [Some(0.5), Some(-1.)]);
+    }
+
+    #[test]
+    fn default_methods_are_unsupported() {
+        struct NoState;
+        impl Parameters for NoState {}
+
+        assert_eq!(
+            NoState.serialize(&mut Vec::new()).unwrap_err().kind(),
+            io::ErrorKind::Unsupported,
+        );
+        assert!(matches!(
+            NoState.deserialize(&mut [].as_slice()),
+            Err(DeserializeError::Unsupported)
+        ));
+    }
+}
@@ -0,0 +1,1547 @@
+use super::*;
+use buffer::Buffers;
+use processor::{MemoryReport, ParamArray, Parameters, Processor};
+use simd_util::simd::{
+    cmp::SimdPartialEq, num::SimdFloat, LaneCount, Mask, Simd, SupportedLaneCount,
+};
+
+#[cfg(feature = "std")]
+use core::array;
+#[cfg(feature = "std")]
+use processor::DeserializeError;
+#[cfg(feature = "std")]
+use simd_util::simd::cmp::SimdPartialOrd;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+/// Lanes of `buf` containing at least one non-zero sample
+#[inline]
+fn active_lanes<const N: usize>(buf: &[Simd<f32, N>]) -> Mask<i32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    buf.iter().fold(Mask::splat(false), |mask, sample| {
+        mask | sample.simd_ne(Simd::splat(0.))
+    })
+}
+
+/// Lanes of the given stereo voice, each voice spanning two adjacent lanes
+#[cfg(feature = "std")]
+#[inline]
+fn voice_lanes<const N: usize>(voice_idx: usize) -> Mask<i32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    Mask::from_array(array::from_fn(|lane| lane / 2 == voice_idx))
+}
+
+/// Copies every input to the output with the same index, silencing
+/// outputs whose input is disconnected.
+///
+/// An output may share its buffer with the input of the same index (the copy is
+/// then skipped), but not with any other input, as copying to it could overwrite
+/// that input before it is read. This is only checked in debug builds.
+pub struct Passthrough<const N: usize> {
+    num_ports: usize,
+}
+
+impl<const N: usize> Passthrough<N> {
+    #[inline]
+    pub fn new(num_ports: usize) -> Self {
+        Self { num_ports }
+    }
+}
+
+impl<const N: usize> Processor for Passthrough<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    type Sample = Simd<f32, N>;
+
+    #[inline]
+    fn audio_io_layout(&self) -> (usize, usize) {
+        (self.num_ports, self.num_ports)
+    }
+
+    fn process(&mut self, mut buffers: Buffers<Self::Sample>, _cluster_idx: usize) -> Mask<i32, N> {
+        let num_ports = self.num_ports;
+        debug_assert!(
+            (0..num_ports).all(|i| (0..num_ports).all(|j| i == j || !buffers.aliases(j, i))),
+            "Passthrough: an output shares its buffer with an input of a different index",
+        );
+
+        let mut mask = Mask::splat(false);
+
+        for i in 0..num_ports {
+            if buffers.copy(i, i).is_err() {
+                let _ = buffers.fill_output(i, Simd::splat(0.));
+            }
+
+            if let Ok(output) = buffers.output(i) {
+                mask |= active_lanes(output);
+            }
+        }
+
+        mask
+    }
+
+    #[inline]
+    fn parameters(&self) -> Arc<dyn Parameters> {
+        Arc::new(())
+    }
+
+    #[inline]
+    fn initialize(&mut self, _sr: f32, _max_buffer_size: usize, _max_num_clusters: usize) -> usize {
+        0
+    }
+
+    #[inline]
+    fn memory_requirements(
+        &self,
+        _sr: f32,
+        _max_buffer_size: usize,
+        _max_num_clusters: usize,
+    ) -> MemoryReport {
+        MemoryReport::Bytes(0)
+    }
+
+    #[inline]
+    fn reset(&mut self, _index: (usize, usize)) {}
+}
+
+/// Sums all its inputs into its only output. Any number of inputs may
+/// share their buffer with the output.
+pub struct Mix<const N: usize> {
+    num_inputs: usize,
+}
+
+impl<const N: usize> Mix<N> {
+    #[inline]
+    pub fn new(num_inputs: usize) -> Self {
+        Self { num_inputs }
+    }
+}
+
+impl<const N: usize> Processor for Mix<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    type Sample = Simd<f32, N>;
+
+    #[inline]
+    fn audio_io_layout(&self) -> (usize, usize) {
+        (self.num_inputs, 1)
+    }
+
+    fn process(&mut self, mut buffers: Buffers<Self::Sample>, _cluster_idx: usize) -> Mask<i32, N> {
+        if buffers.output(0).is_err() {
+            return Mask::splat(false);
+        }
+
+        // inputs sharing their buffer with the output are already in it, so it is
+        // accumulated into, after being scaled by their count, instead of overwritten
+        let num_aliased = (0..self.num_inputs)
+            .filter(|&i| buffers.aliases(i, 0))
+            .count();
+
+        let first = if num_aliased == 0 {
+            let Some(first) = (0..self.num_inputs).find(|&i| buffers.input(i).is_ok()) else {
+                let _ = buffers.fill_output(0, Simd::splat(0.));
+                return Mask::splat(false);
+            };
+
+            let _ = buffers.copy(first, 0);
+            Some(first)
+        } else {
+            if num_aliased > 1 {
+                let scale = Simd::splat(num_aliased as f32);
+                for sample in buffers.output(0).unwrap() {
+                    *sample *= scale;
+                }
+            }
+            None
+        };
+
+        for i in 0..self.num_inputs {
+            if Some(i) != first && !buffers.aliases(i, 0) {
+                let _ = buffers.mix(i, 0, Simd::splat(1.));
+            }
+        }
+
+        active_lanes(buffers.output(0).unwrap())
+    }
+
+    #[inline]
+    fn parameters(&self) -> Arc<dyn Parameters> {
+        Arc::new(())
+    }
+
+    #[inline]
+    fn initialize(&mut self, _sr: f32, _max_buffer_size: usize, _max_num_clusters: usize) -> usize {
+        0
+    }
+
+    #[inline]
+    fn memory_requirements(
+        &self,
+        _sr: f32,
+        _max_buffer_size: usize,
+        _max_num_clusters: usize,
+    ) -> MemoryReport {
+        MemoryReport::Bytes(0)
+    }
+
+    #[inline]
+    fn reset(&mut self, _index: (usize, usize)) {}
+}
+
+#[derive(Debug)]
+pub struct GainParams {
+    values: ParamArray<1>,
+}
+
+impl Default for GainParams {
+    #[inline]
+    fn default() -> Self {
+        Self::new(1.)
+    }
+}
+
+impl GainParams {
+    pub const GAIN_ID: u64 = 0;
+
+    #[inline]
+    pub fn new(gain: f32) -> Self {
+        Self {
+            values: ParamArray::new([gain]),
+        }
+    }
+
+    #[inline]
+    pub fn gain(&self) -> f32 {
+        self.values.get(Self::GAIN_ID).unwrap()
+    }
+
+    #[inline]
+    pub fn set_gain(&self, gain: f32) {
+        self.values.set(Self::GAIN_ID, gain);
+    }
+}
+
+impl Parameters for GainParams {
+    #[cfg(feature = "std")]
+    #[inline]
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.values.serialize(writer)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn deserialize(&self, reader: &mut dyn Read) -> Result<(), DeserializeError> {
+        self.values.deserialize(reader)
+    }
+}
+
+/// Multiplies its input by a gain parameter, ramping linearly over
+/// each block to avoid zipper noise when it changes.
+///
+/// This doesn't use `simd_util`'s `LogSmoother`: it moves geometrically, by the
+/// ratio of the target to the current value, which is undefined when either of them
+/// is 0. Muting (a gain of 0) is one of the most common uses of this processor,
+/// ramping to and from it must work.
+pub struct Gain<const N: usize> {
+    params: Arc<GainParams>,
+    cluster_gains: Box<[f32]>,
+}
+
+impl<const N: usize> Default for Gain<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Arc::default())
+    }
+}
+
+impl<const N: usize> Gain<N> {
+    #[inline]
+    pub fn new(params: Arc<GainParams>) -> Self {
+        Self {
+            params,
+            cluster_gains: Box::default(),
+        }
+    }
+}
+
+impl<const N: usize> Processor for Gain<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    type Sample = Simd<f32, N>;
+
+    #[inline]
+    fn audio_io_layout(&self) -> (usize, usize) {
+        (1, 1)
+    }
+
+    fn process(&mut self, mut buffers: Buffers<Self::Sample>, cluster_idx: usize) -> Mask<i32, N> {
+        if buffers.copy(0, 0).is_err() {
+            let _ = buffers.fill_output(0, Simd::splat(0.));
+        }
+
+        let target = self.params.gain();
+        let current = &mut self.cluster_gains[cluster_idx];
+        let start = *current;
+        *current = target;
+
+        let Ok(output) = buffers.output(0) else {
+            return Mask::splat(false);
+        };
+
+        let step = (target - start) / output.len() as f32;
+
+        for (i, sample) in output.iter_mut().enumerate() {
+            *sample *= Simd::splat(start + step * (i + 1) as f32);
+        }
+
+        active_lanes(output)
+    }
+
+    #[inline]
+    fn parameters(&self) -> Arc<dyn Parameters> {
+        self.params.clone()
+    }
+
+    #[inline]
+    fn initialize(&mut self, _sr: f32, _max_buffer_size: usize, max_num_clusters: usize) -> usize {
+        self.cluster_gains = iter::repeat_n(self.params.gain(), max_num_clusters).collect();
+        0
+    }
+
+    #[inline]
+    fn try_initialize(
+        &mut self,
+        _sr: f32,
+        _max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> Result<usize, AllocError> {
+        let gain = self.params.gain();
+        self.cluster_gains = try_boxed_slice(max_num_clusters, || Ok(gain))?;
+        Ok(0)
+    }
+
+    #[inline]
+    fn memory_requirements(
+        &self,
+        _sr: f32,
+        _max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> MemoryReport {
+        MemoryReport::Bytes(max_num_clusters * mem::size_of::<f32>())
+    }
+
+    #[inline]
+    fn reset(&mut self, (cluster_idx, _voice_idx): (usize, usize)) {
+        self.cluster_gains[cluster_idx] = self.params.gain();
+    }
+}
+
+/// Parameters of two processors, serialized one after the other
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+struct PairParams {
+    first: Arc<dyn Parameters>,
+    second: Arc<dyn Parameters>,
+}
+
+impl PairParams {
+    #[inline]
+    // `dyn Parameters` isn't `Send + Sync`, so neither is this
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn new(first: Arc<dyn Parameters>, second: Arc<dyn Parameters>) -> Arc<Self> {
+        Arc::new(Self { first, second })
+    }
+}
+
+impl Parameters for PairParams {
+    #[cfg(feature = "std")]
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.first.serialize(writer)?;
+        self.second.serialize(writer)
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize(&self, reader: &mut dyn Read) -> Result<(), DeserializeError> {
+        self.first.deserialize(reader)?;
+        self.second.deserialize(reader)
+    }
+}
+
+/// Runs `A`, then `B` on `A`'s outputs, as a single processor.
+///
+/// `A`'s outputs are passed to `B` through the chain's outputs, `B` then processes
+/// them in place. Thus, `B` must have as many inputs as `A` has outputs, and
+/// at least as many outputs as inputs. If one of the chain's outputs is disconnected,
+/// so is the corresponding input of `B`.
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Processor, B: Processor<Sample = A::Sample>> Chain<A, B> {
+    /// # Panics
+    ///
+    /// If the IO layouts of `first` and `second` aren't compatible, see above
+    #[inline]
+    pub fn new(first: A, second: B) -> Self {
+        let (_, a_out) = first.audio_io_layout();
+        let (b_in, b_out) = second.audio_io_layout();
+        assert!(
+            a_out == b_in && b_in <= b_out,
+            "incompatible IO layouts: {a_out} outputs chained into ({b_in}, {b_out})",
+        );
+        Self { first, second }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: Processor, B: Processor<Sample = A::Sample>> Processor for Chain<A, B> {
+    type Sample = A::Sample;
+
+    #[inline]
+    fn audio_io_layout(&self) -> (usize, usize) {
+        (
+            self.first.audio_io_layout().0,
+            self.second.audio_io_layout().1,
+        )
+    }
+
+    #[inline]
+    fn process(
+        &mut self,
+        mut buffers: Buffers<Self::Sample>,
+        cluster_idx: usize,
+    ) -> <Self::Sample as SimdFloat>::Mask {
+        self.first.process(buffers.reborrow(), cluster_idx);
+        self.second
+            .process(buffers.outputs_as_inputs(), cluster_idx)
+    }
+
+    #[inline]
+    fn parameters(&self) -> Arc<dyn Parameters> {
+        PairParams::new(self.first.parameters(), self.second.parameters())
+    }
+
+    #[inline]
+    fn initialize(&mut self, sr: f32, max_buffer_size: usize, max_num_clusters: usize) -> usize {
+        self.first.initialize(sr, max_buffer_size, max_num_clusters)
+            + self
+                .second
+                .initialize(sr, max_buffer_size, max_num_clusters)
+    }
+
+    #[inline]
+    fn try_initialize(
+        &mut self,
+        sr: f32,
+        max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> Result<usize, AllocError> {
+        Ok(self
+            .first
+            .try_initialize(sr, max_buffer_size, max_num_clusters)?
+            + self
+                .second
+                .try_initialize(sr, max_buffer_size, max_num_clusters)?)
+    }
+
+    #[inline]
+    fn memory_requirements(
+        &self,
+        sr: f32,
+        max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> MemoryReport {
+        self.first
+            .memory_requirements(sr, max_buffer_size, max_num_clusters)
+            .combine(
+                self.second
+                    .memory_requirements(sr, max_buffer_size, max_num_clusters),
+            )
+    }
+
+    #[inline]
+    fn reset(&mut self, index: (usize, usize)) {
+        self.first.reset(index);
+        self.second.reset(index);
+    }
+}
+
+/// Runs `A` and `B` side by side, as a single processor. Its inputs are
+/// `A`'s inputs followed by `B`'s, and likewise for its outputs.
+pub struct Parallel<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Parallel<A, B> {
+    #[inline]
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A, B, const N: usize> Processor for Parallel<A, B>
+where
+    A: Processor<Sample = Simd<f32, N>>,
+    B: Processor<Sample = Simd<f32, N>>,
+    LaneCount<N>: SupportedLaneCount,
+{
+    type Sample = Simd<f32, N>;
+
+    #[inline]
+    fn audio_io_layout(&self) -> (usize, usize) {
+        let (a_in, a_out) = self.first.audio_io_layout();
+        let (b_in, b_out) = self.second.audio_io_layout();
+        (a_in + b_in, a_out + b_out)
+    }
+
+    /// # Panics
+    ///
+    /// If `buffers` has fewer ports than `self.audio_io_layout()`
+    #[inline]
+    fn process(&mut self, mut buffers: Buffers<Self::Sample>, cluster_idx: usize) -> Mask<i32, N> {
+        let (a_in, a_out) = self.first.audio_io_layout();
+        let (b_in, b_out) = self.second.audio_io_layout();
+        let missing_ports = "Parallel: fewer ports than in its IO layout";
+
+        let first = buffers.ports(0..a_in, 0..a_out).expect(missing_ports);
+        let first_mask = self.first.process(first, cluster_idx);
+
+        let second = buffers
+            .ports(a_in..a_in + b_in, a_out..a_out + b_out)
+            .expect(missing_ports);
+        first_mask | self.second.process(second, cluster_idx)
+    }
+
+    #[inline]
+    fn parameters(&self) -> Arc<dyn Parameters> {
+        PairParams::new(self.first.parameters(), self.second.parameters())
+    }
+
+    /// Returns the latency of the slowest processor
+    #[inline]
+    fn initialize(&mut self, sr: f32, max_buffer_size: usize, max_num_clusters: usize) -> usize {
+        self.first
+            .initialize(sr, max_buffer_size, max_num_clusters)
+            .max(
+                self.second
+                    .initialize(sr, max_buffer_size, max_num_clusters),
+            )
+    }
+
+    #[inline]
+    fn try_initialize(
+        &mut self,
+        sr: f32,
+        max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> Result<usize, AllocError> {
+        Ok(self
+            .first
+            .try_initialize(sr, max_buffer_size, max_num_clusters)?
+            .max(
+                self.second
+                    .try_initialize(sr, max_buffer_size, max_num_clusters)?,
+            ))
+    }
+
+    #[inline]
+    fn memory_requirements(
+        &self,
+        sr: f32,
+        max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> MemoryReport {
+        self.first
+            .memory_requirements(sr, max_buffer_size, max_num_clusters)
+            .combine(
+                self.second
+                    .memory_requirements(sr, max_buffer_size, max_num_clusters),
+            )
+    }
+
+    #[inline]
+    fn reset(&mut self, index: (usize, usize)) {
+        self.first.reset(index);
+        self.second.reset(index);
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct AdsrParams {
+    values: ParamArray<4>,
+}
+
+#[cfg(feature = "std")]
+impl Default for AdsrParams {
+    #[inline]
+    fn default() -> Self {
+        Self::new(0.005, 0.1, 0.7, 0.2)
+    }
+}
+
+#[cfg(feature = "std")]
+impl AdsrParams {
+    pub const ATTACK_ID: u64 = 0;
+    pub const DECAY_ID: u64 = 1;
+    pub const SUSTAIN_ID: u64 = 2;
+    pub const RELEASE_ID: u64 = 3;
+
+    /// Times are in seconds, `sustain` is a linear level in `[0, 1]`
+    #[inline]
+    pub fn new(attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        Self {
+            values: ParamArray::new([attack, decay, sustain, release]),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, id: u64) -> Option<f32> {
+        self.values.get(id)
+    }
+
+    /// Returns `false`, and does nothing, if `id` doesn't exist
+    #[inline]
+    pub fn set(&self, id: u64, value: f32) -> bool {
+        self.values.set(id, value)
+    }
+
+    #[inline]
+    pub fn attack(&self) -> f32 {
+        self.get(Self::ATTACK_ID).unwrap()
+    }
+
+    #[inline]
+    pub fn decay(&self) -> f32 {
+        self.get(Self::DECAY_ID).unwrap()
+    }
+
+    #[inline]
+    pub fn sustain(&self) -> f32 {
+        self.get(Self::SUSTAIN_ID).unwrap()
+    }
+
+    #[inline]
+    pub fn release(&self) -> f32 {
+        self.get(Self::RELEASE_ID).unwrap()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Parameters for AdsrParams {
+    #[inline]
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.values.serialize(writer)
+    }
+
+    #[inline]
+    fn deserialize(&self, reader: &mut dyn Read) -> Result<(), DeserializeError> {
+        self.values.deserialize(reader)
+    }
+}
+
+/// What an [`Adsr`] writes to its output
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdsrMode {
+    /// The envelope itself, no inputs
+    Generate,
+    /// The (only) input, multiplied by the envelope
+    Multiply,
+}
+
+/// The attack segment aims past 1 so that it reaches it in finite time
+#[cfg(feature = "std")]
+const ATTACK_TARGET: f32 = 1.2;
+/// `log2(1 - 1 / ATTACK_TARGET)`
+#[cfg(feature = "std")]
+const LOG2_ATTACK_RATIO: f32 = -2.584_962_5;
+/// -80 dB, below which released voices are considered finished
+#[cfg(feature = "std")]
+const MIN_LEVEL: f32 = 1e-4;
+/// `log2(MIN_LEVEL)`
+#[cfg(feature = "std")]
+const LOG2_MIN_LEVEL: f32 = -13.287_712;
+
+/// Per-sample multiplier making an exponential segment cover all but
+/// `2^log2_ratio` of the distance to its target in `seconds`
+#[cfg(feature = "std")]
+#[inline]
+fn segment_coef(log2_ratio: f32, seconds: f32, sr: f32) -> f32 {
+    (log2_ratio / (seconds * sr).max(1.)).exp2()
+}
+
+#[cfg(feature = "std")]
+struct AdsrCoefs<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    attack: Simd<f32, N>,
+    decay: Simd<f32, N>,
+    sustain: Simd<f32, N>,
+    release: Simd<f32, N>,
+}
+
+#[cfg(feature = "std")]
+struct AdsrVoices<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    level: Simd<f32, N>,
+    active: Mask<i32, N>,
+    attack: Mask<i32, N>,
+    release: Mask<i32, N>,
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> Default for AdsrVoices<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            level: Simd::splat(0.),
+            active: Mask::splat(false),
+            attack: Mask::splat(false),
+            release: Mask::splat(false),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> AdsrVoices<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    #[inline]
+    fn tick(&mut self, coefs: &AdsrCoefs<N>) -> Simd<f32, N> {
+        let one = Simd::splat(1.);
+        let attack_target = Simd::splat(ATTACK_TARGET);
+
+        let attack = attack_target + (self.level - attack_target) * coefs.attack;
+        // decay and sustain, following changes of the sustain level smoothly
+        let hold = coefs.sustain + (self.level - coefs.sustain) * coefs.decay;
+        let release = self.level * coefs.release;
+
+        let level = self
+            .attack
+            .select(attack, self.release.select(release, hold));
+
+        let attack_done = self.attack & level.simd_ge(one);
+        self.attack &= !attack_done;
+        let level = attack_done.select(one, level);
+
+        let release_done = self.release & level.simd_lt(Simd::splat(MIN_LEVEL));
+        self.release &= !release_done;
+        self.active &= !release_done;
+
+        self.level = self.active.select(level, Simd::splat(0.));
+        self.level
+    }
+}
+
+/// An ADSR envelope, with exponential segments, for every lane of every cluster.
+///
+/// The returned mask contains the lanes whose envelope is still running, a lane
+/// is cleared as soon as its release segment falls below -80 dB.
+#[cfg(feature = "std")]
+pub struct Adsr<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    params: Arc<AdsrParams>,
+    mode: AdsrMode,
+    sr: f32,
+    clusters: Box<[AdsrVoices<N>]>,
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> Adsr<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    #[inline]
+    pub fn new(params: Arc<AdsrParams>, mode: AdsrMode) -> Self {
+        Self {
+            params,
+            mode,
+            sr: 44100.,
+            clusters: Box::default(),
+        }
+    }
+
+    /// Starts the attack segment of the given lanes, from their current level
+    #[inline]
+    pub fn note_on(&mut self, cluster_idx: usize, lanes: Mask<i32, N>) {
+        let voices = &mut self.clusters[cluster_idx];
+        voices.active |= lanes;
+        voices.attack |= lanes;
+        voices.release &= !lanes;
+    }
+
+    /// Starts the release segment of the given lanes, if they are active
+    #[inline]
+    pub fn note_off(&mut self, cluster_idx: usize, lanes: Mask<i32, N>) {
+        let voices = &mut self.clusters[cluster_idx];
+        let lanes = lanes & voices.active;
+        voices.release |= lanes;
+        voices.attack &= !lanes;
+    }
+
+    #[inline]
+    pub fn active_lanes(&self, cluster_idx: usize) -> Mask<i32, N> {
+        self.clusters[cluster_idx].active
+    }
+
+    #[inline]
+    fn coefs(&self) -> AdsrCoefs<N> {
+        let p = &self.params;
+        AdsrCoefs {
+            attack: Simd::splat(segment_coef(LOG2_ATTACK_RATIO, p.attack(), self.sr)),
+            decay: Simd::splat(segment_coef(LOG2_MIN_LEVEL, p.decay(), self.sr)),
+            sustain: Simd::splat(p.sustain().clamp(0., 1.)),
+            release: Simd::splat(segment_coef(LOG2_MIN_LEVEL, p.release(), self.sr)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> Processor for Adsr<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    type Sample = Simd<f32, N>;
+
+    #[inline]
+    fn audio_io_layout(&self) -> (usize, usize) {
+        match self.mode {
+            AdsrMode::Generate => (0, 1),
+            AdsrMode::Multiply => (1, 1),
+        }
+    }
+
+    fn process(&mut self, mut buffers: Buffers<Self::Sample>, cluster_idx: usize) -> Mask<i32, N> {
+        if self.mode == AdsrMode::Multiply && buffers.copy(0, 0).is_err() {
+            let _ = buffers.fill_output(0, Simd::splat(0.));
+        }
+
+        let coefs = self.coefs();
+        let len = buffers.len().get();
+        let voices = &mut self.clusters[cluster_idx];
+
+        match buffers.output(0) {
+            Ok(output) => match self.mode {
+                AdsrMode::Generate => {
+                    for sample in output {
+                        *sample = voices.tick(&coefs);
+                    }
+                }
+                AdsrMode::Multiply => {
+                    for sample in output {
+                        *sample *= voices.tick(&coefs);
+                    }
+                }
+            },
+            // keep the envelopes running, even if no one is listening
+            Err(_) => {
+                for _ in 0..len {
+                    voices.tick(&coefs);
+                }
+            }
+        }
+
+        voices.active
+    }
+
+    #[inline]
+    fn parameters(&self) -> Arc<dyn Parameters> {
+        self.params.clone()
+    }
+
+    #[inline]
+    fn initialize(&mut self, sr: f32, _max_buffer_size: usize, max_num_clusters: usize) -> usize {
+        self.sr = sr;
+        self.clusters = iter::repeat_with(AdsrVoices::default)
+            .take(max_num_clusters)
+            .collect();
+        0
+    }
+
+    #[inline]
+    fn try_initialize(
+        &mut self,
+        sr: f32,
+        _max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> Result<usize, AllocError> {
+        self.clusters = try_boxed_slice(max_num_clusters, || Ok(AdsrVoices::default()))?;
+        self.sr = sr;
+        Ok(0)
+    }
+
+    #[inline]
+    fn memory_requirements(
+        &self,
+        _sr: f32,
+        _max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> MemoryReport {
+        MemoryReport::Bytes(max_num_clusters * mem::size_of::<AdsrVoices<N>>())
+    }
+
+    /// Silences the two lanes of the given stereo voice
+    #[inline]
+    fn reset(&mut self, (cluster_idx, voice_idx): (usize, usize)) {
+        let lanes = !voice_lanes(voice_idx);
+        let voices = &mut self.clusters[cluster_idx];
+        voices.level = lanes.select(voices.level, Simd::splat(0.));
+        voices.active &= lanes;
+        voices.attack &= lanes;
+        voices.release &= lanes;
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct LimiterParams {
+    values: ParamArray<2>,
+}
+
+#[cfg(feature = "std")]
+impl Default for LimiterParams {
+    #[inline]
+    fn default() -> Self {
+        Self::new(0., 0.1)
+    }
+}
+
+#[cfg(feature = "std")]
+impl LimiterParams {
+    pub const THRESHOLD_ID: u64 = 0;
+    pub const RELEASE_ID: u64 = 1;
+
+    /// `threshold` is in dBFS, `release` is the release time constant, in seconds
+    #[inline]
+    pub fn new(threshold: f32, release: f32) -> Self {
+        Self {
+            values: ParamArray::new([threshold, release]),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, id: u64) -> Option<f32> {
+        self.values.get(id)
+    }
+
+    /// Returns `false`, and does nothing, if `id` doesn't exist
+    #[inline]
+    pub fn set(&self, id: u64, value: f32) -> bool {
+        self.values.set(id, value)
+    }
+
+    #[inline]
+    pub fn threshold(&self) -> f32 {
+        self.get(Self::THRESHOLD_ID).unwrap()
+    }
+
+    #[inline]
+    pub fn release(&self) -> f32 {
+        self.get(Self::RELEASE_ID).unwrap()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Parameters for LimiterParams {
+    #[inline]
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.values.serialize(writer)
+    }
+
+    #[inline]
+    fn deserialize(&self, reader: &mut dyn Read) -> Result<(), DeserializeError> {
+        self.values.deserialize(reader)
+    }
+}
+
+/// Swaps each pair of adjacent lanes, so that each lane of a stereo voice sees the other one
+#[cfg(feature = "std")]
+#[inline]
+fn swap_stereo<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let x = x.to_array();
+    Simd::from_array(array::from_fn(|i| x[(i ^ 1).min(N - 1)]))
+}
+
+#[cfg(feature = "std")]
+struct LimiterState<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    gain: Simd<f32, N>,
+    threshold: f32,
+}
+
+/// A peak limiter, without lookahead (and thus, without latency), applied in place.
+///
+/// Both lanes of a stereo voice share the same gain, computed from their peak level.
+/// Gain reduction is applied instantly, and released exponentially. Changes of the
+/// threshold are ramped linearly over each block.
+#[cfg(feature = "std")]
+pub struct Limiter<const N: usize>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    params: Arc<LimiterParams>,
+    sr: f32,
+    clusters: Box<[LimiterState<N>]>,
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> Default for Limiter<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new(Arc::default())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> Limiter<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    #[inline]
+    pub fn new(params: Arc<LimiterParams>) -> Self {
+        Self {
+            params,
+            sr: 44100.,
+            clusters: Box::default(),
+        }
+    }
+
+    /// Current gain of each lane of the given cluster, 1 meaning no gain reduction,
+    /// for metering. Both lanes of a stereo voice always have the same gain.
+    #[inline]
+    pub fn gain_reduction(&self, cluster_idx: usize) -> Simd<f32, N> {
+        self.clusters[cluster_idx].gain
+    }
+
+    #[inline]
+    fn threshold_gain(&self) -> f32 {
+        10f32.powf(self.params.threshold() / 20.)
+    }
+
+    #[inline]
+    fn initial_state(&self) -> LimiterState<N> {
+        LimiterState {
+            gain: Simd::splat(1.),
+            threshold: self.threshold_gain(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> Processor for Limiter<N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    type Sample = Simd<f32, N>;
+
+    #[inline]
+    fn audio_io_layout(&self) -> (usize, usize) {
+        (1, 1)
+    }
+
+    fn process(&mut self, mut buffers: Buffers<Self::Sample>, cluster_idx: usize) -> Mask<i32, N> {
+        if buffers.copy(0, 0).is_err() {
+            let _ = buffers.fill_output(0, Simd::splat(0.));
+        }
+
+        let target = self.threshold_gain();
+        let release = Simd::splat((-1. / (self.params.release() * self.sr).max(1.)).exp());
+        let state = &mut self.clusters[cluster_idx];
+        let start = state.threshold;
+        state.threshold = target;
+
+        let Ok(output) = buffers.output(0) else {
+            return Mask::splat(false);
+        };
+
+        let one = Simd::splat(1.);
+        let step = (target - start) / output.len() as f32;
+
+        for (i, sample) in output.iter_mut().enumerate() {
+            let threshold = Simd::splat(start + step * (i + 1) as f32);
+            let level = sample.abs();
+            let peak = level.simd_max(swap_stereo(level));
+
+            // silent lanes yield infinity here, thus, unity gain
+            let required = (threshold / peak).simd_min(one);
+            let released = one - (one - state.gain) * release;
+            state.gain = released.simd_min(required);
+
+            *sample *= state.gain;
+        }
+
+        active_lanes(output)
+    }
+
+    #[inline]
+    fn parameters(&self) -> Arc<dyn Parameters> {
+        self.params.clone()
+    }
+
+    #[inline]
+    fn initialize(&mut self, sr: f32, _max_buffer_size: usize, max_num_clusters: usize) -> usize {
+        self.sr = sr;
+        self.clusters = iter::repeat_with(|| self.initial_state())
+            .take(max_num_clusters)
+            .collect();
+        0
+    }
+
+    #[inline]
+    fn try_initialize(
+        &mut self,
+        sr: f32,
+        _max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> Result<usize, AllocError> {
+        self.clusters = try_boxed_slice(max_num_clusters, || Ok(self.initial_state()))?;
+        self.sr = sr;
+        Ok(0)
+    }
+
+    #[inline]
+    fn memory_requirements(
+        &self,
+        _sr: f32,
+        _max_buffer_size: usize,
+        max_num_clusters: usize,
+    ) -> MemoryReport {
+        MemoryReport::Bytes(max_num_clusters * mem::size_of::<LimiterState<N>>())
+    }
+
+    /// Releases all gain reduction of the given stereo voice
+    #[inline]
+    fn reset(&mut self, (cluster_idx, voice_idx): (usize, usize)) {
+        let state = &mut self.clusters[cluster_idx];
+        state.gain = voice_lanes(voice_idx).select(Simd::splat(1.), state.gain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use buffer::BufferList;
+    use simd_util::simd::f32x4;
+    use testing::BuffersBuilder;
+
+    const SR: f32 = 48000.;
+    const LEN: NonZeroUsize = NonZeroUsize::new(4).unwrap();
+
+    fn splats(values: &[f32]) -> Vec<f32x4> {
+        values.iter().copied().map(f32x4::splat).collect()
+    }
+
+    /// Runs `processor` on buffers holding `data`, wired according to `inputs` and `outputs`
+    fn process_wired<P: Processor<Sample = f32x4>>(
+        processor: &mut P,
+        data: &[&[f32]],
+        inputs: &[usize],
+        outputs: &[usize],
+    ) -> (BufferList<f32x4, <f32x4 as SimdFloat>::Bits>, Mask<i32, 4>) {
+        let mut list = BufferList::new_vfloat_default(data.len(), LEN);
+        for (i, data) in data.iter().enumerate() {
+            list.get_mut(i).unwrap().0.copy_from_slice(&splats(data));
+        }
+
+        let mask = processor.process(Buffers::new((&mut list).into(), inputs, outputs), 0);
+        (list, mask)
+    }
+
+    #[test]
+    fn gain_ramp() {
+        let params = Arc::new(GainParams::new(1.));
+        let mut gain = Gain::<4>::new(params.clone());
+        gain.initialize(SR, LEN.get(), 1);
+
+        let mut bufs = BuffersBuilder::new(LEN)
+            .input(&splats(&[1., -2., 4., 0.]))
+            .output()
+            .build();
+
+        gain.process(bufs.buffers(), 0);
+        assert_eq!(bufs.output(0).unwrap(), splats(&[1., -2., 4., 0.]));
+
+        // ramps linearly towards the new gain over the next block
+        params.set_gain(0.5);
+        gain.process(bufs.buffers(), 0);
+        assert_eq!(bufs.output(0).unwrap(), splats(&[0.875, -1.5, 2.5, 0.]));
+
+        gain.process(bufs.buffers(), 0);
+        assert_eq!(bufs.output(0).unwrap(), splats(&[0.5, -1., 2., 0.]));
+    }
+
+    #[test]
+    fn gain_ramps_to_and_from_zero() {
+        let params = Arc::new(GainParams::new(1.));
+        let mut gain = Gain::<4>::new(params.clone());
+        gain.initialize(SR, LEN.get(), 1);
+
+        let mut bufs = BuffersBuilder::new(LEN)
+            .input(&splats(&[1.; 4]))
+            .output()
+            .build();
+
+        params.set_gain(0.);
+        gain.process(bufs.buffers(), 0);
+        assert_eq!(bufs.output(0).unwrap(), splats(&[0.75, 0.5, 0.25, 0.]));
+
+        let mask = gain.process(bufs.buffers(), 0);
+        assert_eq!(bufs.output(0).unwrap(), splats(&[0.; 4]));
+        assert!(!mask.any());
+
+        params.set_gain(1.);
+        gain.process(bufs.buffers(), 0);
+        assert_eq!(bufs.output(0).unwrap(), splats(&[0.25, 0.5, 0.75, 1.]));
+    }
+
+    #[test]
+    fn mix_sums_connected_inputs() {
+        let mut mix = Mix::<4>::new(4);
+        let mut bufs = BuffersBuilder::new(LEN)
+            .input(&splats(&[1., 2., 3., 4.]))
+            .disconnected_input()
+            .input(&splats(&[10., 20., 30., 40.]))
+            .input(&splats(&[-1., -2., -3., -4.]))
+            .output()
+            .build();
+
+        let mask = mix.process(bufs.buffers(), 0);
+        assert_eq!(bufs.output(0).unwrap(), splats(&[10., 20., 30., 40.]));
+        assert_eq!(mask, Mask::splat(true));
+    }
+
+    #[test]
+    fn mix_inputs_aliasing_output() {
+        let a = [1., 2., 3., 4.];
+        let b = [10., 20., 30., 40.];
+
+        // one input in the output's buffer
+        let (list, _) = process_wired(&mut Mix::new(2), &[&a, &b], &[1, 0], &[0]);
+        assert_eq!(list.get(0).unwrap().0, splats(&[11., 22., 33., 44.]));
+
+        // two of them, along with another input reading the same buffer
+        let (list, _) = process_wired(&mut Mix::new(4), &[&a, &b], &[0, 1, 0, 0], &[0]);
+        assert_eq!(list.get(0).unwrap().0, splats(&[13., 26., 39., 52.]));
+    }
+
+    #[test]
+    fn mix_without_inputs_is_silent() {
+        let (list, mask) = process_wired(&mut Mix::new(2), &[&[1.; 4]], &[usize::MAX; 2], &[0]);
+        assert_eq!(list.get(0).unwrap().0, splats(&[0.; 4]));
+        assert_eq!(mask, Mask::splat(false));
+    }
+
+    #[test]
+    fn passthrough_silences_disconnected_inputs() {
+        let a = [1., 2., 3., 4.];
+        let stale = [5.; 4];
+
+        let (list, mask) = process_wired(
+            &mut Passthrough::new(3),
+            &[&a, &stale, &stale, &stale],
+            &[0, usize::MAX, 3],
+            &[1, 2, 3],
+        );
+
+        assert_eq!(list.get(1).unwrap().0, splats(&a));
+        assert_eq!(list.get(2).unwrap().0, splats(&[0.; 4]));
+        // aliasing the input with the same index
+        assert_eq!(list.get(3).unwrap().0, splats(&stale));
+        assert_eq!(mask, Mask::splat(true));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "different index"]
+    fn passthrough_rejects_cross_aliasing() {
+        let data: [&[f32]; 3] = [&[1.; 4], &[2.; 4], &[3.; 4]];
+        process_wired(&mut Passthrough::new(2), &data, &[0, 1], &[1, 2]);
+    }
+
+    #[test]
+    fn chain_layout() {
+        let chain = Chain::new(Gain::<4>::default(), Passthrough::new(1));
+        assert_eq!(chain.audio_io_layout(), (1, 1));
+
+        let chain = Chain::new(Mix::<4>::new(3), Passthrough::new(1));
+        assert_eq!(chain.audio_io_layout(), (3, 1));
+    }
+
+    #[test]
+    #[should_panic = "incompatible IO layouts"]
+    fn chain_rejects_mismatched_outputs() {
+        Chain::new(Passthrough::<4>::new(2), Mix::new(3));
+    }
+
+    #[test]
+    #[should_panic = "incompatible IO layouts"]
+    fn chain_rejects_too_few_outputs() {
+        Chain::new(Passthrough::<4>::new(2), Mix::new(2));
+    }
+
+    #[test]
+    fn chain_process() {
+        let mut chain = Chain::new(Mix::<4>::new(2), Gain::new(Arc::new(GainParams::new(0.5))));
+        chain.initialize(SR, LEN.get(), 1);
+
+        let mut bufs = BuffersBuilder::new(LEN)
+            .input(&splats(&[1., 2., 3., 4.]))
+            .input(&splats(&[3., 2., 1., 0.]))
+            .output()
+            .build();
+
+        chain.process(bufs.buffers(), 0);
+        assert_eq!(bufs.output(0).unwrap(), splats(&[2.; 4]));
+    }
+
+    #[test]
+    fn parallel() {
+        let mut parallel = Parallel::new(
+            Gain::<4>::new(Arc::new(GainParams::new(2.))),
+            Passthrough::new(2),
+        );
+        assert_eq!(parallel.audio_io_layout(), (3, 3));
+        parallel.initialize(SR, LEN.get(), 1);
+
+        let mut bufs = BuffersBuilder::new(LEN)
+            .input(&splats(&[1.; 4]))
+            .disconnected_input()
+            .input(&splats(&[3.; 4]))
+            .output()
+            .output()
+            .output()
+            .build();
+
+        parallel.process(bufs.buffers(), 0);
+        assert_eq!(bufs.output(0).unwrap(), splats(&[2.; 4]));
+        assert_eq!(bufs.output(1).unwrap(), splats(&[0.; 4]));
+        assert_eq!(bufs.output(2).unwrap(), splats(&[3.; 4]));
+    }
+
+    fn assert_memory_requirements(mut processor: impl Processor) {
+        let report = processor.memory_requirements(SR, 64, 16);
+        let (result, allocated) =
+            test_alloc::allocated_bytes(|| processor.try_initialize(SR, 64, 16));
+
+        result.unwrap();
+        assert_eq!(report, MemoryReport::Bytes(allocated));
+    }
+
+    #[test]
+    fn gain_memory_requirements() {
+        assert_memory_requirements(Gain::<4>::default());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn adsr_memory_requirements() {
+        assert_memory_requirements(Adsr::<4>::new(Arc::default(), AdsrMode::Generate));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn limiter_memory_requirements() {
+        assert_memory_requirements(Limiter::<4>::default());
+    }
+
+    #[cfg(feature = "std")]
+    fn db(db: f32) -> f32 {
+        10f32.powf(db / 20.)
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn limiter_step() {
+        const QUIET: usize = 64;
+        const LOUD: usize = 64;
+        const LEN: usize = 2048;
+        // 10 ms
+        const TAU: usize = 480;
+
+        let quiet = f32x4::from_array([db(-20.), -db(-20.), db(-20.), 0.]);
+        let loud = f32x4::from_array([db(6.), db(-20.), db(-20.), 0.]);
+
+        let input: Vec<_> = iter::repeat_n(quiet, QUIET)
+            .chain(iter::repeat_n(loud, LOUD))
+            .chain(iter::repeat(quiet))
+            .take(LEN)
+            .collect();
+
+        let mut limiter = Limiter::<4>::new(Arc::new(LimiterParams::new(-3., 0.01)));
+        limiter.initialize(SR, LEN, 1);
+
+        let mut bufs = BuffersBuilder::new(NonZeroUsize::new(LEN).unwrap())
+            .input(&input)
+            .output()
+            .build();
+        limiter.process(bufs.buffers(), 0);
+        let output = bufs.output(0).unwrap();
+
+        // unity, below the threshold
+        assert_eq!(output[..QUIET], input[..QUIET]);
+
+        let threshold = db(-3.);
+        for sample in &output[QUIET..QUIET + LOUD] {
+            let [left, right, other, silent] = sample.to_array();
+            assert!((left - threshold).abs() <= threshold * 1e-6, "{left}");
+            // both lanes of a stereo voice get the same gain reduction
+            assert!((right / db(-20.) - left / db(6.)).abs() < 1e-6);
+            // the other voice is unaffected
+            assert_eq!(other, db(-20.));
+            assert_eq!(silent, 0.);
+        }
+
+        // the gain reduction is released with a time constant of TAU samples
+        let reduction = |i: usize| 1. - output[i][0] / input[i][0];
+        let initial = 1. - threshold / db(6.);
+        let first = QUIET + LOUD;
+        assert!((reduction(first) / initial - (-1. / TAU as f32).exp()).abs() < 1e-4);
+        assert!((reduction(first + TAU - 1) / initial - (-1f32).exp()).abs() < 1e-3);
+        assert!(output[first..].iter().all(|s| s[2] == db(-20.)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn limiter_gain_reduction() {
+        let mut limiter = Limiter::<4>::new(Arc::new(LimiterParams::new(-6., 0.01)));
+        limiter.initialize(SR, LEN.get(), 2);
+
+        let mut bufs = BuffersBuilder::new(LEN)
+            .input(&[f32x4::from_array([1., 0.25, 0.25, 0.]); LEN.get()])
+            .output()
+            .build();
+        limiter.process(bufs.buffers(), 1);
+
+        let [left, right, other, silent] = limiter.gain_reduction(1).to_array();
+        assert!((left - db(-6.)).abs() < 1e-6, "{left}");
+        assert_eq!(left, right);
+        assert_eq!([other, silent], [1.; 2]);
+        assert_eq!(limiter.gain_reduction(0), f32x4::splat(1.));
+
+        limiter.reset((1, 0));
+        assert_eq!(limiter.gain_reduction(1), f32x4::splat(1.));
+    }
+
+    /// Runs `adsr` one sample at a time, returning each sample of lane 0 of the
+    /// envelope, along with whether lane 0 was reported as active after it
+    #[cfg(feature = "std")]
+    fn run_adsr(adsr: &mut Adsr<4>, num_samples: usize) -> Vec<(f32, bool)> {
+        let mut bufs = BuffersBuilder::new(NonZeroUsize::MIN).output().build();
+
+        (0..num_samples)
+            .map(|_| {
+                let mask = adsr.process(bufs.buffers(), 0);
+                (bufs.output(0).unwrap()[0][0], mask.test(0))
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn adsr_timing() {
+        const ATTACK: usize = 480;
+        const DECAY: usize = 2400;
+        const RELEASE: usize = 4800;
+        let sustain = 0.5;
+
+        let params = AdsrParams::new(
+            ATTACK as f32 / SR,
+            DECAY as f32 / SR,
+            sustain,
+            RELEASE as f32 / SR,
+        );
+        let mut adsr = Adsr::<4>::new(Arc::new(params), AdsrMode::Generate);
+        adsr.initialize(SR, 1, 1);
+
+        adsr.note_on(0, Mask::from_array([true, true, false, false]));
+        let held = run_adsr(&mut adsr, ATTACK + DECAY + 100);
+
+        // the attack peaks at 1, and the envelope reaches sustain (within -80 dB)
+        // by the end of the decay
+        let peak = held.iter().map(|&(level, _)| level).fold(0., f32::max);
+        assert_eq!(peak, 1.);
+        for &(level, active) in &held[ATTACK + DECAY - 1..] {
+            assert!(
+                (level - sustain).abs() <= 1e-4 * (1. - sustain) + 1e-6,
+                "{level}"
+            );
+            assert!(active);
+        }
+
+        adsr.note_off(0, Mask::splat(true));
+        let released = run_adsr(&mut adsr, RELEASE + 100);
+
+        // the mask is cleared on the exact sample where the level falls below
+        // -80 dB (and is flushed to zero), within the release time
+        let end = released.iter().position(|&(_, active)| !active).unwrap();
+        assert!(end < RELEASE, "{end}");
+        assert!(released[..end]
+            .iter()
+            .all(|&(level, active)| level >= 1e-4 && active));
+        assert!(released[end..]
+            .iter()
+            .all(|&(level, active)| level == 0. && !active));
+        assert!(released[end - 1].0 * adsr.coefs().release[0] < 1e-4);
+
+        // the other voice never started
+        assert_eq!(adsr.active_lanes(0), Mask::splat(false));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn adsr_reset_voice() {
+        let mut adsr = Adsr::<64>::new(Arc::default(), AdsrMode::Generate);
+        adsr.initialize(SR, 1, 1);
+        adsr.note_on(0, Mask::splat(true));
+
+        adsr.reset((0, 20));
+        adsr.reset((0, 31));
+        // voices with no lanes in this vector are ignored
+        adsr.reset((0, 40));
+
+        let active = adsr.active_lanes(0);
+        for lane in 0..64 {
+            assert_eq!(
+                active.test(lane),
+                ![40, 41, 62, 63].contains(&lane),
+                "{lane}"
+            );
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use super::*;
+use buffer::{BufferList, Buffers};
+use simd_util::simd::num::SimdFloat;
+
+/// Builds the buffers needed to call [`Processor::process`](crate::processor::Processor::process)
+/// outside of a graph, for unit-testing processors.
+///
+/// ```
+/// # #![feature(portable_simd)]
+/// use core::num::NonZeroUsize;
+/// use mythril::testing::BuffersBuilder;
+/// use simd_util::simd::f32x4;
+///
+/// let mut bufs = BuffersBuilder::new(NonZeroUsize::new(16).unwrap())
+///     .input(&[f32x4::splat(0.5); 16])
+///     .disconnected_input()
+///     .output()
+///     .build();
+///
+/// // this is where `processor.process(bufs.buffers(), 0)` would go
+/// let mut buffers = bufs.buffers();
+/// assert!(buffers.input(1).is_err());
+/// let sample = buffers.input(0).unwrap().0[0];
+/// buffers.output(0).unwrap().fill(sample * f32x4::splat(2.));
+///
+/// assert!(bufs.output(0).unwrap().iter().all(|&s| s == f32x4::splat(1.)));
+/// ```
+pub struct BuffersBuilder<T> {
+    buf_len: NonZeroUsize,
+    inputs: Vec<Option<Box<[T]>>>,
+    outputs: Vec<bool>,
+}
+
+impl<T: SimdFloat> BuffersBuilder<T> {
+    #[inline]
+    #[must_use]
+    pub fn new(buf_len: NonZeroUsize) -> Self {
+        Self {
+            buf_len,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Adds a connected input, its buffer starting with `data`, and zero-filled afterwards.
+    ///
+    /// # Panics
+    ///
+    /// If `data` is longer than the buffer length.
+    #[inline]
+    #[must_use]
+    pub fn input(mut self, data: &[T]) -> Self {
+        assert!(
+            data.len() <= self.buf_len.get(),
+            "input data is longer than the buffer length"
+        );
+        self.inputs.push(Some(data.into()));
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn disconnected_input(mut self) -> Self {
+        self.inputs.push(None);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn output(mut self) -> Self {
+        self.outputs.push(true);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn disconnected_output(mut self) -> Self {
+        self.outputs.push(false);
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn build(self) -> TestBuffers<T>
+    where
+        T::Bits: Default,
+    {
+        let num_inputs = self.inputs.iter().flatten().count();
+        let num_outputs = self.outputs.iter().filter(|&&connected| connected).count();
+
+        let mut list = BufferList::new_vfloat_default(num_inputs + num_outputs, self.buf_len);
+
+        let mut next = 0;
+        let mut next_index = || {
+            let i = next;
+            next += 1;
+            i
+        };
+
+        let inputs = self
+            .inputs
+            .into_iter()
+            .map(|maybe_data| {
+                maybe_data.map_or(usize::MAX, |data| {
+                    let i = next_index();
+                    list.get_mut(i).unwrap().0[..data.len()].copy_from_slice(&data);
+                    i
+                })
+            })
+            .collect();
+
+        let outputs = self
+            .outputs
+            .into_iter()
+            .map(|connected| if connected { next_index() } else { usize::MAX })
+            .collect();
+
+        TestBuffers {
+            list,
+            inputs,
+            outputs,
+        }
+    }
+}
+
+/// Owns the buffers created by a [`BuffersBuilder`].
+pub struct TestBuffers<T: SimdFloat> {
+    list: BufferList<T, T::Bits>,
+    inputs: Box<[usize]>,
+    outputs: Box<[usize]>,
+}
+
+impl<T: SimdFloat> TestBuffers<T> {
+    #[inline]
+    pub fn buffers(&mut self) -> Buffers<T> {
+        Buffers::new((&mut self.list).into(), &self.inputs, &self.outputs)
+    }
+
+    /// Returns `None` if the input doesn't exist or is disconnected.
+    #[inline]
+    pub fn input(&self, index: usize) -> Option<&[T]> {
+        self.get(&self.inputs, index)
+    }
+
+    /// Returns `None` if the output doesn't exist or is disconnected.
+    #[inline]
+    pub fn output(&self, index: usize) -> Option<&[T]> {
+        self.get(&self.outputs, index)
+    }
+
+    #[inline]
+    fn get(&self, ports: &[usize], index: usize) -> Option<&[T]> {
+        ports
+            .get(index)
+            .and_then(|&i| self.list.get(i))
+            .map(|(buf, _)| buf)
+    }
+}